@@ -0,0 +1,76 @@
+use bevy::{core_pipeline::core_3d::graph::Node3d, prelude::*, window::PrimaryWindow};
+use bevy_egui::{backdrop::EguiBackdropEffect, EguiContexts, EguiPlugin, EguiRenderOrder};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, (rotate_cubes, ui_example_system))
+        .run();
+}
+
+#[derive(Component)]
+struct Rotating;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+) {
+    let cube = meshes.add(Cuboid::new(1.0, 1.0, 1.0));
+    for index in 0..5 {
+        let x = index as f32 * 1.5 - 3.0;
+        commands.spawn((
+            PbrBundle {
+                mesh: cube.clone(),
+                material: materials.add(Color::hsl(index as f32 * 72.0, 0.8, 0.5)),
+                transform: Transform::from_xyz(x, 0.0, 0.0),
+                ..default()
+            },
+            Rotating,
+        ));
+    }
+    commands.spawn(PointLightBundle {
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..default()
+    });
+    let camera = commands
+        .spawn(Camera3dBundle {
+            transform: Transform::from_xyz(0.0, 2.0, 7.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        })
+        .id();
+
+    // Swap chain textures can't be read, so the UI is rendered into the main texture of
+    // the camera instead.
+    commands
+        .entity(primary_window.single())
+        .insert(EguiRenderOrder::in_camera(
+            camera,
+            Node3d::Tonemapping,
+            Node3d::EndMainPassPostProcessing,
+        ));
+}
+
+fn rotate_cubes(time: Res<Time>, mut cubes: Query<&mut Transform, With<Rotating>>) {
+    for mut transform in cubes.iter_mut() {
+        transform.rotate_y(time.delta_seconds());
+    }
+}
+
+fn ui_example_system(mut contexts: EguiContexts, mut blur_radius: Local<Option<f32>>) {
+    let blur_radius = blur_radius.get_or_insert(12.0);
+    egui::Window::new("Frosted glass")
+        // The backdrop is applied to the frame, which is shown by `EguiBackdropEffect::show`.
+        .frame(egui::Frame::none())
+        .title_bar(false)
+        .show(contexts.ctx_mut(), |ui| {
+            let frame = egui::Frame::window(ui.style()).fill(egui::Color32::from_black_alpha(96));
+            EguiBackdropEffect::blur(*blur_radius).show(ui, frame, |ui| {
+                ui.label("The scene behind this window is blurred");
+                ui.add(egui::Slider::new(blur_radius, 0.0..=32.0).text("Blur radius"));
+            });
+        });
+}
@@ -0,0 +1,99 @@
+use bevy::{input::mouse::MouseButton, prelude::*, render::view::RenderLayers};
+use bevy_egui::{
+    camera_viewport::{EguiCameraViewport, EguiCameraViewportInput},
+    EguiContexts, EguiPlugin,
+};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, (ui_example_system, orbit_camera_system))
+        .run();
+}
+
+#[derive(Component)]
+struct OrbitCamera {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // The viewport scene is rendered on a separate layer, so that the primary window camera
+    // doesn't render it.
+    let viewport_layer = RenderLayers::layer(1);
+
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Cuboid::new(2.0, 2.0, 2.0)),
+            material: materials.add(Color::srgb(0.8, 0.7, 0.6)),
+            ..default()
+        },
+        viewport_layer.clone(),
+    ));
+    commands.spawn((
+        PointLightBundle {
+            transform: Transform::from_xyz(4.0, 8.0, 4.0),
+            ..default()
+        },
+        viewport_layer.clone(),
+    ));
+    commands.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                order: -1,
+                ..default()
+            },
+            ..default()
+        },
+        OrbitCamera {
+            yaw: 0.5,
+            pitch: 0.5,
+            distance: 8.0,
+        },
+        EguiCameraViewport::default().with_input_forwarding(true),
+        viewport_layer,
+    ));
+
+    // The primary window camera, which renders only the UI.
+    commands.spawn(Camera2dBundle::default());
+}
+
+fn ui_example_system(mut contexts: EguiContexts, mut viewports: Query<&mut EguiCameraViewport>) {
+    egui::Window::new("Viewport")
+        .default_size([400.0, 300.0])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label("Drag to orbit, scroll to zoom");
+            for mut viewport in viewports.iter_mut() {
+                viewport.show(ui);
+            }
+        });
+}
+
+fn orbit_camera_system(
+    mut viewport_input: EventReader<EguiCameraViewportInput>,
+    mut cameras: Query<(&mut OrbitCamera, &mut Transform)>,
+) {
+    for input in viewport_input.read() {
+        let Ok((mut orbit, _)) = cameras.get_mut(input.camera) else {
+            continue;
+        };
+        if input.drag_button == Some(MouseButton::Left) {
+            orbit.yaw -= input.drag_delta.x * 0.01;
+            orbit.pitch = (orbit.pitch + input.drag_delta.y * 0.01).clamp(-1.5, 1.5);
+        }
+        orbit.distance = (orbit.distance - input.scroll_delta.y * 0.01).clamp(2.0, 20.0);
+    }
+
+    for (orbit, mut transform) in cameras.iter_mut() {
+        let rotation = Quat::from_euler(EulerRot::YXZ, orbit.yaw, -orbit.pitch, 0.0);
+        *transform = Transform::from_translation(rotation * Vec3::Z * orbit.distance)
+            .looking_at(Vec3::ZERO, Vec3::Y);
+    }
+}
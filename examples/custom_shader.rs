@@ -0,0 +1,42 @@
+use bevy::{
+    asset::{embedded_asset, AssetPath},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_egui::{EguiContexts, EguiPlugin, EguiShader};
+use std::path::Path;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, ui_example_system);
+    embedded_asset!(app, "examples/", "custom_shader.wgsl");
+    app.run();
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let shader = asset_server.load(
+        AssetPath::from_path(Path::new("custom_shader/custom_shader.wgsl")).with_source("embedded"),
+    );
+    commands
+        .entity(primary_window.single())
+        .insert(EguiShader::new(shader).with_params(Vec4::new(1.0, 0.0, 0.0, 0.0)));
+}
+
+fn ui_example_system(mut contexts: EguiContexts, mut shaders: Query<&mut EguiShader>) {
+    let Ok(mut shader) = shaders.get_single_mut() else {
+        return;
+    };
+    egui::Window::new("Custom shader").show(contexts.ctx_mut(), |ui| {
+        ui.add(egui::Slider::new(&mut shader.params.x, 0.2..=1.0).text("Opacity"));
+        ui.add(egui::Slider::new(&mut shader.params.y, 0.0..=1.0).text("Desaturation"));
+    });
+}
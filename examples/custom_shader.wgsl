@@ -0,0 +1,11 @@
+#import bevy_egui::common::{VertexOutput, egui_fragment, transform}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = egui_fragment(in);
+    // `x` is the opacity of the UI, `y` is the amount of desaturation.
+    let luminance = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let rgb = mix(color.rgb, vec3<f32>(luminance), transform.shader_params.y);
+    // Colors are premultiplied, so all the channels are faded.
+    return vec4<f32>(rgb, color.a) * transform.shader_params.x;
+}
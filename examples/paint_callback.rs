@@ -12,8 +12,10 @@ use bevy::{
     },
 };
 use bevy_egui::{
-    egui_node::{EguiBevyPaintCallback, EguiBevyPaintCallbackImpl, EguiPipelineKey},
-    EguiContexts, EguiPlugin, EguiRenderToTextureHandle,
+    egui_node::{
+        EguiBevyPaintCallback, EguiBevyPaintCallbackImpl, EguiPaintCallbackTarget, EguiPipelineKey,
+    },
+    EguiClearColor, EguiContexts, EguiPlugin, EguiRenderToTextureHandle,
 };
 use std::path::Path;
 use wgpu_types::{Extent3d, TextureUsages};
@@ -53,7 +55,7 @@ impl EguiBevyPaintCallbackImpl for CustomPaintCallback {
         &self,
         _info: egui::PaintCallbackInfo,
         window_entity: Entity,
-        key: EguiPipelineKey,
+        target: &EguiPaintCallbackTarget,
         world: &mut World,
     ) {
         let pipeline_id =
@@ -68,7 +70,7 @@ impl EguiBevyPaintCallbackImpl for CustomPaintCallback {
                     let pipeline_id = specialized_custom_pipelines.specialize(
                         pipeline_cache,
                         specialized_pipeline,
-                        key,
+                        target.pipeline_key,
                     );
 
                     world
@@ -87,7 +89,7 @@ impl EguiBevyPaintCallbackImpl for CustomPaintCallback {
         _info: egui::PaintCallbackInfo,
         render_pass: &mut bevy::render::render_phase::TrackedRenderPass<'pass>,
         window_entity: Entity,
-        _key: EguiPipelineKey,
+        _target: &EguiPaintCallbackTarget,
         world: &'pass World,
     ) {
         let Some(pipeline) = world
@@ -147,7 +149,10 @@ impl SpecializedRenderPipeline for CustomPipeline {
                 conservative: false,
             },
             depth_stencil: None,
-            multisample: MultisampleState::default(),
+            multisample: MultisampleState {
+                count: key.sample_count,
+                ..Default::default()
+            },
             fragment: Some(FragmentState {
                 shader: self.shader.clone(),
                 shader_defs: vec![],
@@ -214,7 +219,10 @@ fn setup_worldspace(
         }),
         ..default()
     });
-    commands.spawn(EguiRenderToTextureHandle(output_texture));
+    commands.spawn((
+        EguiRenderToTextureHandle(output_texture),
+        EguiClearColor::Transparent,
+    ));
     commands.spawn(Camera3dBundle {
         transform: Transform::from_xyz(1.5, 1.5, 1.5).looking_at(Vec3::new(0., 0., 0.), Vec3::Y),
         ..default()
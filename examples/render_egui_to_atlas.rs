@@ -0,0 +1,107 @@
+use bevy::{math::Affine2, prelude::*};
+use bevy_egui::{
+    EguiContexts, EguiPlugin, EguiRenderToTextureAtlas, EguiRenderToTextureAtlasRegion,
+};
+use wgpu_types::{Extent3d, TextureUsages};
+
+const LABEL_COUNT: usize = 16;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins);
+    app.add_plugins(EguiPlugin);
+    app.add_systems(Startup, setup_labels);
+    app.add_systems(Update, (update_labels, update_label_materials));
+    app.run();
+}
+
+#[derive(Component)]
+struct Label {
+    index: usize,
+    material: Handle<StandardMaterial>,
+}
+
+fn update_labels(time: Res<Time>, mut contexts: EguiContexts, labels: Query<(Entity, &Label)>) {
+    for (entity, label) in labels.iter() {
+        egui::CentralPanel::default().show(contexts.ctx_for_entity_mut(entity), |ui| {
+            ui.label(format!("Label #{}", label.index));
+            let health = (time.elapsed_seconds() * 0.3 + label.index as f32 * 0.1).fract();
+            ui.add(egui::ProgressBar::new(1.0 - health));
+        });
+    }
+}
+
+// Regions are allocated by the plugin, materials pick up their UV rects once they're known.
+fn update_label_materials(
+    labels: Query<
+        (&EguiRenderToTextureAtlasRegion, &Label),
+        Changed<EguiRenderToTextureAtlasRegion>,
+    >,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (region, label) in labels.iter() {
+        let (Some(uv_rect), Some(material)) =
+            (region.uv_rect(), materials.get_mut(&label.material))
+        else {
+            continue;
+        };
+        material.uv_transform =
+            Affine2::from_scale_angle_translation(uv_rect.size(), 0.0, uv_rect.min);
+    }
+}
+
+fn setup_labels(
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    let atlas_texture = images.add({
+        let size = Extent3d {
+            width: 512,
+            height: 512,
+            depth_or_array_layers: 1,
+        };
+        let mut atlas_texture = Image {
+            data: vec![0; (size.width * size.height * 4) as usize],
+            ..default()
+        };
+        atlas_texture.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+        atlas_texture.texture_descriptor.size = size;
+        atlas_texture
+    });
+    // All the labels are drawn into this image in a single render pass.
+    let atlas = commands
+        .spawn(EguiRenderToTextureAtlas::new(Handle::clone(&atlas_texture)))
+        .id();
+
+    let mesh = meshes.add(Rectangle::new(1.0, 0.5));
+    for index in 0..LABEL_COUNT {
+        let material = materials.add(StandardMaterial {
+            base_color_texture: Some(Handle::clone(&atlas_texture)),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        });
+        let position = Vec3::new(
+            (index % 4) as f32 * 1.2 - 1.8,
+            (index / 4) as f32 * 0.7 - 1.05,
+            0.0,
+        );
+        commands.spawn((
+            PbrBundle {
+                mesh: Handle::clone(&mesh),
+                material: Handle::clone(&material),
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            EguiRenderToTextureAtlasRegion::new(atlas, Vec2::new(120.0, 60.0)),
+            Label { index, material },
+        ));
+    }
+
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 0.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+}
@@ -1,5 +1,8 @@
 use bevy::prelude::*;
-use bevy_egui::{EguiContexts, EguiPlugin, EguiRenderToTextureHandle};
+use bevy_egui::{
+    EguiClearColor, EguiContexts, EguiPlugin, EguiRenderToTextureHandle,
+    EguiRenderToTextureSampling,
+};
 use wgpu_types::{Extent3d, TextureUsages};
 
 fn main() {
@@ -61,7 +64,12 @@ fn setup_worldspace(
         }),
         ..default()
     });
-    commands.spawn(EguiRenderToTextureHandle(output_texture));
+    // Mipmaps and anisotropic filtering keep the texture readable at a grazing angle.
+    commands.spawn((
+        EguiRenderToTextureHandle(output_texture),
+        EguiRenderToTextureSampling::default(),
+        EguiClearColor::Transparent,
+    ));
     commands.spawn(Camera3dBundle {
         transform: Transform::from_xyz(1.5, 1.5, 1.5).looking_at(Vec3::new(0., 0., 0.), Vec3::Y),
         ..default()
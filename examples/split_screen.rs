@@ -0,0 +1,96 @@
+use bevy::{
+    prelude::*,
+    render::camera::{ClearColorConfig, Viewport},
+    window::WindowResized,
+};
+use bevy_egui::{EguiCameraContext, EguiContext, EguiPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, (set_camera_viewports, player_hud_system))
+        .run();
+}
+
+#[derive(Component)]
+struct Player(usize);
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Plane3d::default().mesh().size(10.0, 10.0)),
+        material: materials.add(Color::srgb(0.3, 0.5, 0.3)),
+        ..default()
+    });
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+        material: materials.add(Color::srgb(0.8, 0.7, 0.6)),
+        transform: Transform::from_xyz(0.0, 0.5, 0.0),
+        ..default()
+    });
+    commands.spawn(PointLightBundle {
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..default()
+    });
+
+    for (index, position) in [Vec3::new(-5.0, 4.0, 5.0), Vec3::new(5.0, 4.0, 5.0)]
+        .into_iter()
+        .enumerate()
+    {
+        commands.spawn((
+            Camera3dBundle {
+                camera: Camera {
+                    order: index as isize,
+                    // Don't clear the viewport of the first camera.
+                    clear_color: if index == 0 {
+                        ClearColorConfig::Default
+                    } else {
+                        ClearColorConfig::None
+                    },
+                    ..default()
+                },
+                transform: Transform::from_translation(position).looking_at(Vec3::ZERO, Vec3::Y),
+                ..default()
+            },
+            Player(index + 1),
+            // Each camera gets its own Egui context rendered into its viewport.
+            EguiCameraContext::default(),
+        ));
+    }
+}
+
+fn set_camera_viewports(
+    windows: Query<&Window>,
+    mut resize_events: EventReader<WindowResized>,
+    mut cameras: Query<(&mut Camera, &Player)>,
+) {
+    for resize_event in resize_events.read() {
+        let Ok(window) = windows.get(resize_event.window) else {
+            continue;
+        };
+        let size = window.physical_size();
+        for (mut camera, player) in cameras.iter_mut() {
+            camera.viewport = Some(Viewport {
+                physical_position: UVec2::new((player.0 as u32 - 1) * size.x / 2, 0),
+                physical_size: UVec2::new(size.x / 2, size.y),
+                ..default()
+            });
+        }
+    }
+}
+
+fn player_hud_system(mut contexts: Query<(&mut EguiContext, &Player)>) {
+    for (mut context, player) in contexts.iter_mut() {
+        egui::Window::new(format!("Player {}", player.0)).show(context.get_mut(), |ui| {
+            ui.label("Each player viewport has its own Egui context.");
+            if ui.button("Click me").clicked() {
+                info!("Player {} clicked the button", player.0);
+            }
+        });
+    }
+}
@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use bevy_egui::{world_space::EguiWorldSpaceContext, EguiContexts, EguiPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, (ui_panel_system, rotate_panel_system))
+        .run();
+}
+
+#[derive(Component)]
+struct Panel;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Plane3d::default().mesh().size(8.0, 8.0)),
+        material: materials.add(Color::srgb(0.3, 0.5, 0.3)),
+        ..default()
+    });
+    // The cube intersects the panel, showing that the UI is depth tested.
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Cuboid::new(0.8, 0.8, 0.8)),
+        material: materials.add(Color::srgb(0.8, 0.7, 0.6)),
+        transform: Transform::from_xyz(0.8, 1.0, 0.0),
+        ..default()
+    });
+    commands.spawn(PointLightBundle {
+        point_light: PointLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..default()
+    });
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(-1.5, 2.5, 4.5)
+            .looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+        ..default()
+    });
+
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_xyz(0.0, 1.2, 0.0)),
+        EguiWorldSpaceContext::new(Vec2::new(320.0, 240.0)).with_units_per_point(0.008),
+        Panel,
+    ));
+}
+
+fn ui_panel_system(
+    time: Res<Time>,
+    mut contexts: EguiContexts,
+    panels: Query<Entity, With<Panel>>,
+) {
+    for panel in panels.iter() {
+        egui::CentralPanel::default().show(contexts.ctx_for_entity_mut(panel), |ui| {
+            ui.heading("World space UI");
+            ui.separator();
+            ui.label(format!("Elapsed: {:.1} s", time.elapsed_seconds()));
+            ui.add(egui::ProgressBar::new(time.elapsed_seconds().fract()).show_percentage());
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for i in 0..20 {
+                    ui.label(format!("Clipped row {i}"));
+                }
+            });
+        });
+    }
+}
+
+fn rotate_panel_system(time: Res<Time>, mut panels: Query<&mut Transform, With<Panel>>) {
+    for mut transform in panels.iter_mut() {
+        transform.rotation = Quat::from_rotation_y(time.elapsed_seconds().sin() * 0.6);
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+/// Blurs what has been rendered behind a part of the UI, for frosted glass panels.
+///
+/// The effect is a paint callback the Egui render node recognizes: when it's drawn, the node
+/// samples the content the render target had before the Egui pass, so the effect shows what
+/// cameras have rendered, but not the Egui shapes beneath it.
+///
+/// The render target has to be readable, which is the case for render to texture contexts,
+/// cameras rendering to images and contexts rendered inside a camera graph
+/// (see [`EguiRenderOrder::InCamera`](crate::EguiRenderOrder::InCamera)). Window swap chain
+/// textures can't be read, the effect is skipped with a warning for contexts drawn into them.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{backdrop::EguiBackdropEffect, EguiContexts};
+///
+/// fn ui_system(mut contexts: EguiContexts) {
+///     let ctx = contexts.ctx_mut();
+///     egui::Window::new("Frosted glass")
+///         .frame(egui::Frame::none())
+///         .title_bar(false)
+///         .show(ctx, |ui| {
+///             let frame = egui::Frame::window(ui.style())
+///                 .fill(egui::Color32::from_black_alpha(96));
+///             EguiBackdropEffect::blur(12.0).show(ui, frame, |ui| {
+///                 ui.label("The scene behind this window is blurred");
+///             });
+///         });
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EguiBackdropEffect {
+    /// Blur radius, in points.
+    pub blur_radius: f32,
+    /// Rounding of the affected rect corners, in points.
+    pub rounding: egui::Rounding,
+}
+
+impl EguiBackdropEffect {
+    /// Constructs the blur effect with the radius in points.
+    pub fn blur(blur_radius: f32) -> Self {
+        Self {
+            blur_radius,
+            rounding: egui::Rounding::ZERO,
+        }
+    }
+
+    /// Sets [`EguiBackdropEffect::rounding`].
+    pub fn with_rounding(mut self, rounding: impl Into<egui::Rounding>) -> Self {
+        self.rounding = rounding.into();
+        self
+    }
+
+    /// Creates the paint callback applying the effect to the rect.
+    pub fn paint_callback(self, rect: egui::Rect) -> egui::PaintCallback {
+        egui::PaintCallback {
+            rect,
+            callback: Arc::new(self),
+        }
+    }
+
+    /// Applies the effect to the rect, beneath the shapes painted afterwards.
+    pub fn paint(self, painter: &egui::Painter, rect: egui::Rect) {
+        painter.add(self.paint_callback(rect));
+    }
+
+    /// Shows the contents in the frame, applying the effect behind the frame.
+    ///
+    /// The effect takes the rounding of the frame, which should have a semi-transparent fill
+    /// for the effect to be visible.
+    pub fn show<R>(
+        self,
+        ui: &mut egui::Ui,
+        frame: egui::Frame,
+        add_contents: impl FnOnce(&mut egui::Ui) -> R,
+    ) -> egui::InnerResponse<R> {
+        // Reserve a place beneath the frame background, the rect is known only afterwards.
+        let backdrop = ui.painter().add(egui::Shape::Noop);
+        let rounding = frame.rounding;
+        let response = frame.show(ui, add_contents);
+        ui.painter().set(
+            backdrop,
+            self.with_rounding(rounding)
+                .paint_callback(response.response.rect),
+        );
+        response
+    }
+}
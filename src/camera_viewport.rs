@@ -0,0 +1,231 @@
+use crate::EguiUserTextures;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::prelude::*;
+use bevy_input::mouse::MouseButton;
+use bevy_math::{UVec2, Vec2};
+use bevy_render::{
+    camera::{Camera, RenderTarget},
+    render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+    texture::Image,
+};
+use bevy_utils::HashMap;
+
+/// Renders the output of a camera inside Egui UI.
+///
+/// Insert this component to a camera entity and show it with [`EguiCameraViewport::show`].
+/// The plugin creates the image the camera renders to, registers it in [`EguiUserTextures`]
+/// and resizes it to match the physical size of the widget. Once the component is removed,
+/// the image gets unregistered.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{camera_viewport::EguiCameraViewport, EguiContexts};
+///
+/// fn setup(mut commands: Commands) {
+///     commands.spawn((
+///         Camera3dBundle::default(),
+///         EguiCameraViewport::default().with_input_forwarding(true),
+///     ));
+/// }
+///
+/// fn ui_system(mut contexts: EguiContexts, mut viewports: Query<&mut EguiCameraViewport>) {
+///     egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+///         if let Ok(mut viewport) = viewports.get_single_mut() {
+///             viewport.show(ui);
+///         }
+///     });
+/// }
+/// ```
+#[derive(Component, Default)]
+pub struct EguiCameraViewport {
+    /// Sends [`EguiCameraViewportInput`] events when the widget is hovered or dragged
+    /// (`false` by default).
+    pub forward_input: bool,
+    image: Option<Handle<Image>>,
+    texture_id: Option<egui::TextureId>,
+    requested_size: UVec2,
+    pending_input: Option<PendingInput>,
+}
+
+struct PendingInput {
+    hover_position: Option<Vec2>,
+    drag_button: Option<MouseButton>,
+    drag_delta: Vec2,
+    scroll_delta: Vec2,
+}
+
+/// Input received by an [`EguiCameraViewport`] widget, is sent if
+/// [`EguiCameraViewport::forward_input`] is enabled.
+///
+/// All the values are in Egui points.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct EguiCameraViewportInput {
+    /// Camera entity.
+    pub camera: Entity,
+    /// Pointer position relative to the top left corner of the widget, is set if the widget
+    /// is hovered.
+    pub hover_position: Option<Vec2>,
+    /// Mouse button the widget is dragged with.
+    pub drag_button: Option<MouseButton>,
+    /// Drag delta since the previous frame.
+    pub drag_delta: Vec2,
+    /// Scroll delta, is non-zero only if the widget is hovered.
+    pub scroll_delta: Vec2,
+}
+
+impl EguiCameraViewport {
+    /// Sets [`EguiCameraViewport::forward_input`].
+    pub fn with_input_forwarding(mut self, forward_input: bool) -> Self {
+        self.forward_input = forward_input;
+        self
+    }
+
+    /// Shows the camera output, filling all the available space.
+    pub fn show(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        self.show_sized(ui, ui.available_size())
+    }
+
+    /// Shows the camera output with the specified size.
+    pub fn show_sized(&mut self, ui: &mut egui::Ui, size: egui::Vec2) -> egui::Response {
+        let sense = if self.forward_input {
+            egui::Sense::click_and_drag()
+        } else {
+            egui::Sense::hover()
+        };
+        let (rect, response) = ui.allocate_exact_size(size, sense);
+
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        self.requested_size = UVec2::new(
+            (rect.width() * pixels_per_point).round() as u32,
+            (rect.height() * pixels_per_point).round() as u32,
+        );
+
+        if let Some(texture_id) = self.texture_id {
+            if ui.is_rect_visible(rect) {
+                ui.painter().image(
+                    texture_id,
+                    rect,
+                    egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                    egui::Color32::WHITE,
+                );
+            }
+        }
+
+        if self.forward_input {
+            self.collect_input(ui, &response);
+        }
+
+        response
+    }
+
+    /// Returns the image the camera renders to, is available since the first frame
+    /// the widget was shown.
+    pub fn image(&self) -> Option<&Handle<Image>> {
+        self.image.as_ref()
+    }
+
+    fn collect_input(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let hover_position = response
+            .hover_pos()
+            .map(|pos| pos - response.rect.min)
+            .map(|pos| Vec2::new(pos.x, pos.y));
+        let drag_button = [
+            (egui::PointerButton::Primary, MouseButton::Left),
+            (egui::PointerButton::Secondary, MouseButton::Right),
+            (egui::PointerButton::Middle, MouseButton::Middle),
+        ]
+        .into_iter()
+        .find_map(|(egui_button, button)| response.dragged_by(egui_button).then_some(button));
+        if hover_position.is_none() && drag_button.is_none() {
+            return;
+        }
+
+        let drag_delta = response.drag_delta();
+        let scroll_delta = if response.hovered() {
+            ui.input(|input| input.raw_scroll_delta)
+        } else {
+            egui::Vec2::ZERO
+        };
+        self.pending_input = Some(PendingInput {
+            hover_position,
+            drag_button,
+            drag_delta: Vec2::new(drag_delta.x, drag_delta.y),
+            scroll_delta: Vec2::new(scroll_delta.x, scroll_delta.y),
+        });
+    }
+}
+
+/// Creates and resizes images of [`EguiCameraViewport`] cameras, unregisters the images
+/// of removed viewports and sends [`EguiCameraViewportInput`] events.
+pub fn update_camera_viewports_system(
+    mut viewports: Query<(Entity, &mut EguiCameraViewport, Option<&mut Camera>)>,
+    mut removed_viewports: RemovedComponents<EguiCameraViewport>,
+    mut viewport_images: Local<HashMap<Entity, Handle<Image>>>,
+    mut images: ResMut<Assets<Image>>,
+    mut egui_user_textures: ResMut<EguiUserTextures>,
+    mut viewport_input: EventWriter<EguiCameraViewportInput>,
+) {
+    for entity in removed_viewports.read() {
+        if let Some(image) = viewport_images.remove(&entity) {
+            egui_user_textures.remove_image(&image);
+        }
+    }
+
+    for (camera_entity, mut viewport, camera) in viewports.iter_mut() {
+        if let Some(input) = viewport.pending_input.take() {
+            viewport_input.send(EguiCameraViewportInput {
+                camera: camera_entity,
+                hover_position: input.hover_position,
+                drag_button: input.drag_button,
+                drag_delta: input.drag_delta,
+                scroll_delta: input.scroll_delta,
+            });
+        }
+
+        let requested_size = viewport.requested_size;
+        if requested_size.x == 0 || requested_size.y == 0 {
+            continue;
+        }
+        let size = Extent3d {
+            width: requested_size.x,
+            height: requested_size.y,
+            depth_or_array_layers: 1,
+        };
+
+        let Some(image_handle) = viewport.image.clone() else {
+            let Some(mut camera) = camera else {
+                bevy_utils::warn_once!(
+                    "`EguiCameraViewport` is added to an entity without a camera ({camera_entity:?})"
+                );
+                continue;
+            };
+
+            let image = images.add(camera_viewport_image(size));
+            camera.target = RenderTarget::Image(image.clone());
+            viewport.texture_id = Some(egui_user_textures.add_image(image.clone()));
+            viewport.image = Some(image.clone());
+            viewport_images.insert(camera_entity, image);
+            continue;
+        };
+
+        let Some(image) = images.get(&image_handle) else {
+            continue;
+        };
+        if image.texture_descriptor.size != size {
+            if let Some(image) = images.get_mut(&image_handle) {
+                image.resize(size);
+            }
+        }
+    }
+}
+
+fn camera_viewport_image(size: Extent3d) -> Image {
+    let mut image = Image::default();
+    image.texture_descriptor.label = Some("egui camera viewport image");
+    image.texture_descriptor.dimension = TextureDimension::D2;
+    image.texture_descriptor.format = TextureFormat::Bgra8UnormSrgb;
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image.resize(size);
+    image
+}
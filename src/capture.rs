@@ -0,0 +1,377 @@
+use crate::EguiError;
+use bevy_ecs::prelude::*;
+use bevy_math::UVec2;
+use bevy_render::{
+    extract_component::ExtractComponent,
+    render_asset::RenderAssetUsages,
+    render_resource::{Buffer, Extent3d, MapMode, TextureDimension, TextureFormat},
+    texture::Image,
+};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+/// Requests a capture of what an Egui context paints, without anything rendered beneath it.
+///
+/// Insert the component into the entity of a window, render to texture or camera context: its
+/// UI is drawn once more into an offscreen texture, which is read back asynchronously. When the
+/// image is ready, it's sent as an [`EguiCaptured`] event and the component is removed.
+/// Inserting the component again requests another capture.
+///
+/// Parts of the UI that aren't painted stay transparent, unless the context has an
+/// [`EguiClearColor`](crate::EguiClearColor). Colors of 8-bit images have straight alpha,
+/// float (HDR) images keep premultiplied colors. Contexts drawn into an
+/// [`EguiRenderToTextureAtlas`](crate::EguiRenderToTextureAtlas) or into the world with the
+/// `world_space` feature can't be captured.
+///
+/// ```rust
+/// use bevy::{prelude::*, window::PrimaryWindow};
+/// use bevy_egui::capture::{EguiCapture, EguiCaptured};
+///
+/// fn capture_system(
+///     mut commands: Commands,
+///     keys: Res<ButtonInput<KeyCode>>,
+///     window: Query<Entity, With<PrimaryWindow>>,
+/// ) {
+///     if keys.just_pressed(KeyCode::F12) {
+///         commands
+///             .entity(window.single())
+///             .insert(EguiCapture::save_png("ui.png"));
+///     }
+/// }
+///
+/// fn captured_system(mut captured: EventReader<EguiCaptured>) {
+///     for captured in captured.read() {
+///         info!("Captured {:?}: {:?}", captured.context, captured.image.size());
+///     }
+/// }
+/// ```
+#[derive(Component, Clone, Debug, Default)]
+pub struct EguiCapture {
+    /// Path of a PNG file to save the captured image to, requires the `capture_png` feature.
+    pub png_path: Option<PathBuf>,
+}
+
+impl EguiCapture {
+    /// Constructs the request to capture the UI and save it to a PNG file.
+    pub fn save_png(path: impl Into<PathBuf>) -> Self {
+        Self {
+            png_path: Some(path.into()),
+        }
+    }
+}
+
+impl ExtractComponent for EguiCapture {
+    type QueryData = &'static Self;
+    // The request is extracted once, render nodes keep it until they manage to capture.
+    type QueryFilter = Changed<Self>;
+    type Out = Self;
+
+    fn extract_component(
+        item: bevy_ecs::query::QueryItem<'_, Self::QueryData>,
+    ) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+/// Is sent when the image requested with an [`EguiCapture`] is ready.
+#[derive(Event, Clone, Debug)]
+pub struct EguiCaptured {
+    /// Entity of the context.
+    pub context: Entity,
+    /// Captured UI, has the size of the render target (or the camera viewport) in pixels.
+    pub image: Image,
+}
+
+/// Images read back by the render world, they are sent as [`EguiCaptured`] events in the main
+/// world.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct EguiCaptureResults(Arc<Mutex<Vec<EguiCaptured>>>);
+
+impl EguiCaptureResults {
+    fn push(&self, captured: EguiCaptured) {
+        if let Ok(mut results) = self.0.lock() {
+            results.push(captured);
+        }
+    }
+
+    fn drain(&self) -> Vec<EguiCaptured> {
+        self.0
+            .lock()
+            .map(|mut results| std::mem::take(&mut *results))
+            .unwrap_or_default()
+    }
+}
+
+/// Buffer a captured texture has been copied into.
+pub(crate) struct EguiCaptureReadback {
+    /// Entity of the context.
+    pub(crate) context: Entity,
+    pub(crate) buffer: Buffer,
+    /// Size of the image, in pixels.
+    pub(crate) size: UVec2,
+    /// Rows of the buffer are padded to the copy alignment.
+    pub(crate) padded_bytes_per_row: u32,
+    pub(crate) format: TextureFormat,
+}
+
+impl EguiCaptureReadback {
+    fn image(&self, data: &[u8]) -> Image {
+        captured_image(data, self.size, self.padded_bytes_per_row, self.format)
+    }
+}
+
+/// Builds the image from the data of a readback buffer.
+fn captured_image(
+    data: &[u8],
+    size: UVec2,
+    padded_bytes_per_row: u32,
+    format: TextureFormat,
+) -> Image {
+    use bevy_render::texture::TextureFormatPixelInfo;
+    let bytes_per_row = size.x as usize * format.pixel_size();
+    let mut pixels = Vec::with_capacity(bytes_per_row * size.y as usize);
+    for row in data.chunks(padded_bytes_per_row as usize) {
+        pixels.extend_from_slice(&row[..bytes_per_row]);
+    }
+    unpremultiply_alpha(&mut pixels, format);
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        format,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Captured textures waiting for their command buffers to be submitted.
+#[derive(Resource, Default)]
+pub(crate) struct EguiCaptureReadbacks(Mutex<Vec<EguiCaptureReadback>>);
+
+impl EguiCaptureReadbacks {
+    /// Queues the buffer to be read back once the copy has been submitted.
+    pub(crate) fn push(&self, readback: EguiCaptureReadback) {
+        if let Ok(mut readbacks) = self.0.lock() {
+            readbacks.push(readback);
+        }
+    }
+}
+
+/// Maps the buffers of the captures rendered this frame, images are sent to the main world
+/// once they are read.
+pub(crate) fn map_capture_readbacks_system(
+    readbacks: Res<EguiCaptureReadbacks>,
+    results: Res<EguiCaptureResults>,
+) {
+    let Ok(mut readbacks) = readbacks.0.lock() else {
+        return;
+    };
+    for readback in readbacks.drain(..) {
+        let results = results.clone();
+        let buffer = readback.buffer.clone();
+        buffer.slice(..).map_async(MapMode::Read, move |result| {
+            if let Err(err) = result {
+                bevy_log::error!("Failed to read back the Egui capture: {err}");
+                return;
+            }
+            let image = readback.image(&readback.buffer.slice(..).get_mapped_range());
+            readback.buffer.unmap();
+            results.push(EguiCaptured {
+                context: readback.context,
+                image,
+            });
+        });
+    }
+}
+
+/// Sends [`EguiCaptured`] events and saves the captured images.
+pub(crate) fn forward_captures_system(
+    mut commands: Commands,
+    results: Res<EguiCaptureResults>,
+    captures: Query<&EguiCapture>,
+    mut captured_events: EventWriter<EguiCaptured>,
+    mut egui_errors: EventWriter<EguiError>,
+) {
+    for captured in results.drain() {
+        if let Ok(capture) = captures.get(captured.context) {
+            if let Some(path) = &capture.png_path {
+                if let Err(err) = save_png(&captured.image, path) {
+                    let error = EguiError::CaptureNotSaved {
+                        target: captured.context,
+                        path: path.clone(),
+                    };
+                    bevy_log::error!("{error}: {err}");
+                    egui_errors.send(error);
+                }
+            }
+            commands.entity(captured.context).remove::<EguiCapture>();
+        }
+        captured_events.send(captured);
+    }
+}
+
+#[cfg(feature = "capture_png")]
+fn save_png(image: &Image, path: &std::path::Path) -> Result<(), String> {
+    image
+        .clone()
+        .try_into_dynamic()
+        .map_err(|err| err.to_string())?
+        .save_with_format(path, ::image::ImageFormat::Png)
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "capture_png"))]
+fn save_png(_image: &Image, _path: &std::path::Path) -> Result<(), String> {
+    Err("saving PNG files requires the `capture_png` feature".to_owned())
+}
+
+/// Converts premultiplied colors to straight alpha, which 8-bit images are expected to have.
+fn unpremultiply_alpha(pixels: &mut [u8], format: TextureFormat) {
+    use egui::ecolor::{gamma_u8_from_linear_f32, linear_f32_from_gamma_u8};
+
+    let is_srgb = match format {
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Bgra8UnormSrgb => true,
+        TextureFormat::Rgba8Unorm | TextureFormat::Bgra8Unorm => false,
+        _ => return,
+    };
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = pixel[3] as f32 / 255.0;
+        if alpha == 0.0 || alpha == 1.0 {
+            continue;
+        }
+        for channel in &mut pixel[..3] {
+            // Colors are premultiplied in linear space, sRGB values have to be decoded first.
+            *channel = match is_srgb {
+                true => gamma_u8_from_linear_f32(linear_f32_from_gamma_u8(*channel) / alpha),
+                false => (*channel as f32 / alpha).round().min(255.0) as u8,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EguiPlugin, EguiRenderToTextureHandle};
+    use bevy::{
+        app::{App, PluginGroup},
+        render::{
+            render_resource::TextureUsages,
+            settings::{RenderCreation, WgpuSettings},
+            RenderApp, RenderPlugin,
+        },
+        window::{ExitCondition, WindowPlugin},
+        winit::WinitPlugin,
+        DefaultPlugins,
+    };
+    use bevy_asset::Assets;
+
+    #[test]
+    fn test_captured_image_strips_row_padding() {
+        // Two rows of two opaque pixels, each row padded to 12 bytes.
+        let mut data = Vec::new();
+        for row in 0..2u8 {
+            data.extend_from_slice(&[row, 1, 2, 255, row, 3, 4, 255]);
+            data.extend_from_slice(&[0xAA; 4]);
+        }
+        let image = captured_image(&data, UVec2::new(2, 2), 12, TextureFormat::Rgba8Unorm);
+        assert_eq!(image.size(), UVec2::new(2, 2));
+        assert_eq!(
+            image.data,
+            vec![0, 1, 2, 255, 0, 3, 4, 255, 1, 1, 2, 255, 1, 3, 4, 255]
+        );
+    }
+
+    #[test]
+    fn test_unpremultiply_alpha_unorm() {
+        let mut pixels = vec![20, 10, 0, 51, 10, 20, 30, 255, 0, 0, 0, 0];
+        unpremultiply_alpha(&mut pixels, TextureFormat::Rgba8Unorm);
+        // Opaque and fully transparent pixels are kept as they are.
+        assert_eq!(pixels, vec![100, 50, 0, 51, 10, 20, 30, 255, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_unpremultiply_alpha_srgb() {
+        // Premultiplying a gamma space value of 188 (~0.5 linear) by 0.5 alpha gives ~0.25
+        // linear, which is 137 in gamma space.
+        let mut pixels = vec![137, 0, 255, 128];
+        unpremultiply_alpha(&mut pixels, TextureFormat::Rgba8UnormSrgb);
+        assert!((pixels[0] as i32 - 188).abs() <= 1, "{pixels:?}");
+        assert_eq!(pixels[1], 0);
+        // Values that would exceed white are clamped.
+        assert_eq!(pixels[2], 255);
+        assert_eq!(pixels[3], 128);
+    }
+
+    #[test]
+    fn test_unpremultiply_alpha_skips_float_formats() {
+        let mut pixels = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        unpremultiply_alpha(&mut pixels, TextureFormat::Rgba16Float);
+        assert_eq!(pixels, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_capture_empty_context() {
+        let mut app = App::new();
+        app.add_plugins(
+            DefaultPlugins
+                .set(RenderPlugin {
+                    render_creation: RenderCreation::Automatic(WgpuSettings::default()),
+                    synchronous_pipeline_compilation: true,
+                })
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    ..Default::default()
+                })
+                .build()
+                .disable::<WinitPlugin>(),
+        )
+        .add_plugins(EguiPlugin);
+        // There is no adapter to render with.
+        if app.get_sub_app(RenderApp).is_none() {
+            return;
+        }
+        app.finish();
+        app.cleanup();
+
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: 16,
+                height: 8,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[255, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        image.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+        let handle = app.world_mut().resource_mut::<Assets<Image>>().add(image);
+        let context = app
+            .world_mut()
+            .spawn((EguiRenderToTextureHandle(handle), EguiCapture::default()))
+            .id();
+
+        let mut captured = None;
+        for _ in 0..20 {
+            app.update();
+            let events = app.world().resource::<Events<EguiCaptured>>();
+            if let Some(event) = events.get_reader().read(events).next() {
+                captured = Some(event.clone());
+                break;
+            }
+        }
+        let captured = captured.expect("the capture isn't produced");
+        assert_eq!(captured.context, context);
+        assert_eq!(captured.image.size(), UVec2::new(16, 8));
+        // The UI is empty, what the image contains isn't captured.
+        assert!(captured.image.data.iter().all(|&byte| byte == 0));
+        assert!(app.world().get::<EguiCapture>(context).is_none());
+    }
+}
@@ -1,43 +1,128 @@
-struct Transform {
-    scale: vec2<f32>,
-    translation: vec2<f32>,
+#import bevy_egui::common::{
+    VertexInput, VertexOutput, egui_fragment, egui_vertex, image_sampler, image_texture,
+    linear_from_srgb, srgb_from_linear, transform,
 }
 
-struct VertexInput {
-    @location(0) position: vec2<f32>,
-    @location(1) uv: vec2<f32>,
-    @location(2) color: vec4<f32>,
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    return egui_vertex(in);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return egui_fragment(in);
 }
 
-struct VertexOutput {
+struct CompositeOutput {
     @builtin(position) position: vec4<f32>,
-    @location(0) color: vec4<f32>,
-    @location(1) uv: vec2<f32>,
+    @location(0) uv: vec2<f32>,
 }
 
-@group(0) @binding(0) var<uniform> transform: Transform;
-@group(1) @binding(0) var image_texture: texture_2d<f32>;
-@group(1) @binding(1) var image_sampler: sampler;
+@vertex
+fn vs_composite(@builtin(vertex_index) vertex_index: u32) -> CompositeOutput {
+    // A triangle that covers the whole viewport.
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    return CompositeOutput(vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0), vec2<f32>(uv.x, 1.0 - uv.y));
+}
 
-fn linear_from_srgb(srgb: vec3<f32>) -> vec3<f32> {
-    let cutoff = srgb < vec3<f32>(0.04045);
-    let lower = srgb / 12.92;
-    let higher = pow((srgb + 0.055) / 1.055, vec3<f32>(2.4));
-    return select(higher, lower, cutoff);
+@fragment
+fn fs_composite(in: CompositeOutput) -> @location(0) vec4<f32> {
+#ifdef UPSCALING
+    // The intermediate texture is smaller than the viewport and gets stretched over it.
+    let color = textureSample(image_texture, image_sampler, in.uv);
+#else
+    // The intermediate texture has the same size as the render target.
+    let color = textureLoad(image_texture, vec2<i32>(in.position.xy), 0);
+#endif
+    if color.a <= 0.0 {
+        return vec4<f32>(0.0);
+    }
+#ifdef GAMMA_BLENDING
+    // The texture holds premultiplied gamma space colors.
+    var rgb = linear_from_srgb(color.rgb / color.a) * color.a;
+#ifdef HDR_TARGET
+    rgb *= transform.hdr_brightness;
+#endif
+    return vec4<f32>(rgb, color.a);
+#else
+    return color;
+#endif
+}
+
+struct BackdropInput {
+    @builtin(vertex_index) vertex_index: u32,
+    // Min and max corners, in points.
+    @location(0) rect: vec4<f32>,
+    // Top left, top right, bottom left and bottom right corner radii, in points.
+    @location(1) rounding: vec4<f32>,
+    // Min corner and size of the viewport in the backdrop texture, in UV coordinates.
+    @location(2) viewport_uv: vec4<f32>,
+    @location(3) blur_radius_uv: vec2<f32>,
+}
+
+struct BackdropOutput {
+    @builtin(position) position: vec4<f32>,
+    // Position relative to the rect center, in points.
+    @location(0) local_position: vec2<f32>,
+    @location(1) half_size: vec2<f32>,
+    @location(2) rounding: vec4<f32>,
+    @location(3) uv: vec2<f32>,
+    @location(4) blur_radius_uv: vec2<f32>,
 }
 
 @vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
-    let position = in.position * transform.scale + transform.translation;
-    let color = vec4<f32>(linear_from_srgb(in.color.rgb), in.color.a);
-    return VertexOutput(vec4<f32>(position, 0.0, 1.0), color, in.uv);
+fn vs_backdrop(in: BackdropInput) -> BackdropOutput {
+    let corner = vec2<f32>(f32(in.vertex_index & 1u), f32(in.vertex_index >> 1u));
+    let point = mix(in.rect.xy, in.rect.zw, corner);
+    let position = point * transform.scale + transform.translation;
+    let viewport_position = vec2<f32>(position.x * 0.5 + 0.5, 0.5 - position.y * 0.5);
+    let half_size = (in.rect.zw - in.rect.xy) * 0.5;
+    return BackdropOutput(
+        vec4<f32>(position, 0.0, 1.0),
+        point - in.rect.xy - half_size,
+        half_size,
+        in.rounding,
+        in.viewport_uv.xy + viewport_position * in.viewport_uv.zw,
+        in.blur_radius_uv,
+    );
+}
+
+// Signed distance to a rect with rounded corners.
+fn rounded_rect_distance(position: vec2<f32>, half_size: vec2<f32>, rounding: vec4<f32>) -> f32 {
+    let top = select(rounding.y, rounding.x, position.x < 0.0);
+    let bottom = select(rounding.w, rounding.z, position.x < 0.0);
+    let radius = min(select(bottom, top, position.y < 0.0), min(half_size.x, half_size.y));
+    let q = abs(position) - half_size + radius;
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
 }
 
 @fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let texture_color = textureSample(image_texture, image_sampler, in.uv);
-    // This assumes that texture images are not premultiplied.
-    let color = in.color * vec4<f32>(texture_color.rgb * texture_color.a, texture_color.a);
+fn fs_backdrop(in: BackdropOutput) -> @location(0) vec4<f32> {
+    // A 5x5 gaussian kernel with taps spread over the blur radius, bilinear filtering smooths
+    // the gaps between them.
+    var rgb = vec3<f32>(0.0);
+    var total_weight = 0.0;
+    for (var x = -2; x <= 2; x++) {
+        for (var y = -2; y <= 2; y++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * 0.5;
+            let weight = exp(-dot(offset, offset) * 2.0);
+            let uv = in.uv + offset * in.blur_radius_uv;
+            rgb += textureSampleLevel(image_texture, image_sampler, uv, 0.0).rgb * weight;
+            total_weight += weight;
+        }
+    }
+    rgb /= total_weight;
 
-    return color;
+#ifdef GAMMA_BLENDING
+    // The backdrop texture holds the colors of the render target, the intermediate texture
+    // expects gamma space ones.
+#ifdef HDR_TARGET
+    rgb /= transform.hdr_brightness;
+#endif
+    rgb = srgb_from_linear(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)));
+#endif
+
+    let distance = rounded_rect_distance(in.local_position, in.half_size, in.rounding);
+    let coverage = clamp(0.5 - distance / max(fwidth(distance), 0.0001), 0.0, 1.0);
+    return vec4<f32>(rgb * coverage, coverage);
 }
@@ -0,0 +1,141 @@
+use crate::{
+    egui_node::{
+        begin_render_pass, EguiPipelineKey, EguiRenderNode, EguiRenderTargetSource,
+        EguiRenderTargetView,
+    },
+    EguiRenderToTextureAtlas, EguiRenderToTextureAtlasRegion,
+};
+use bevy_ecs::{prelude::*, world::World};
+use bevy_render::{
+    render_asset::RenderAssets,
+    render_graph::{Node, NodeRunError, RenderGraphContext, RenderLabel},
+    render_resource::LoadOp,
+    renderer::{RenderContext, RenderDevice},
+    texture::GpuImage,
+};
+
+/// [`RenderLabel`] type for the Egui texture atlas pass.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct EguiAtlasPass {
+    /// Index of the atlas entity.
+    pub entity_index: u32,
+    /// Generation of the atlas entity.
+    pub entity_generation: u32,
+}
+
+/// Renders Egui into the region of an [`EguiRenderToTextureAtlas`] allocated for the context.
+///
+/// Region nodes are drawn by an [`EguiAtlasNode`], in the render pass it begins for the atlas.
+#[derive(Clone, Copy, Debug)]
+pub struct EguiAtlasRegionTarget {
+    /// Atlas entity.
+    pub atlas: Entity,
+}
+
+impl EguiAtlasRegionTarget {
+    fn gpu_image<'w>(&self, world: &'w World) -> Option<&'w GpuImage> {
+        let atlas = world.get::<EguiRenderToTextureAtlas>(self.atlas)?;
+        world
+            .get_resource::<RenderAssets<GpuImage>>()?
+            .get(&atlas.image)
+    }
+}
+
+impl EguiRenderTargetSource for EguiAtlasRegionTarget {
+    const PASS_LABEL: &'static str = "egui atlas render pass";
+    const SUPPORTS_INTERMEDIATE_TEXTURE: bool = false;
+
+    fn pipeline_key(&self, _target: Entity, world: &World) -> Option<EguiPipelineKey> {
+        self.gpu_image(world).map(EguiPipelineKey::from_gpu_image)
+    }
+
+    fn view<'w>(&self, target: Entity, world: &'w World) -> Option<EguiRenderTargetView<'w>> {
+        let gpu_image = self.gpu_image(world)?;
+        let viewport = world
+            .get::<EguiRenderToTextureAtlasRegion>(target)?
+            .rect()?;
+        Some(EguiRenderTargetView {
+            texture_view: &gpu_image.texture_view,
+            size: gpu_image.size,
+            viewport,
+            load: LoadOp::Load,
+            texture: None,
+        })
+    }
+}
+
+/// Renders all the regions of an [`EguiRenderToTextureAtlas`] in a single render pass.
+pub struct EguiAtlasNode {
+    atlas: Entity,
+    regions: Vec<EguiRenderNode<EguiAtlasRegionTarget>>,
+}
+
+impl EguiAtlasNode {
+    /// Constructs Egui atlas node.
+    pub fn new(atlas: Entity) -> Self {
+        Self {
+            atlas,
+            regions: Vec::new(),
+        }
+    }
+}
+
+impl Node for EguiAtlasNode {
+    fn update(&mut self, world: &mut World) {
+        let mut regions_query = world.query::<(Entity, &EguiRenderToTextureAtlasRegion)>();
+        let mut region_entities: Vec<Entity> = regions_query
+            .iter(world)
+            .filter(|(_, region)| region.atlas == self.atlas && region.rect().is_some())
+            .map(|(entity, _)| entity)
+            .collect();
+        region_entities.sort();
+
+        // Region nodes keep their buffers between frames, so they're reused for the same entities.
+        self.regions
+            .retain(|node| region_entities.binary_search(&node.target()).is_ok());
+        for entity in region_entities {
+            if !self.regions.iter().any(|node| node.target() == entity) {
+                self.regions.push(EguiRenderNode::with_source(
+                    EguiAtlasRegionTarget { atlas: self.atlas },
+                    entity,
+                ));
+            }
+        }
+
+        for node in &mut self.regions {
+            node.update(world);
+        }
+    }
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let (Some(atlas), Some(images), Some(device)) = (
+            world.get::<EguiRenderToTextureAtlas>(self.atlas),
+            world.get_resource::<RenderAssets<GpuImage>>(),
+            world.get_resource::<RenderDevice>(),
+        ) else {
+            return Ok(());
+        };
+        let Some(gpu_image) = images.get(&atlas.image) else {
+            return Ok(());
+        };
+
+        let mut render_pass = begin_render_pass(
+            render_context.command_encoder(),
+            device,
+            EguiAtlasRegionTarget::PASS_LABEL,
+            &gpu_image.texture_view,
+            None,
+            LoadOp::Clear(wgpu_types::Color::TRANSPARENT),
+            None,
+        );
+        for node in &self.regions {
+            node.draw_in_pass(&mut render_pass, world);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use crate::egui_node::{
+    EguiPipelineKey, EguiRenderNode, EguiRenderTargetSource, EguiRenderTargetView,
+};
+use bevy_ecs::{prelude::*, world::World};
+use bevy_math::{URect, UVec2};
+use bevy_render::{
+    camera::{ExtractedCamera, ManualTextureViews, NormalizedRenderTarget},
+    render_asset::RenderAssets,
+    render_graph::RenderLabel,
+    render_resource::LoadOp,
+    texture::GpuImage,
+    view::{ExtractedWindows, ViewTarget},
+};
+
+/// [`RenderLabel`] type for the Egui camera pass.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct EguiCameraPass {
+    /// Index of the camera entity.
+    pub entity_index: u32,
+    /// Generation of the camera entity.
+    pub entity_generation: u32,
+}
+
+/// Renders Egui into the viewport of a camera with an [`EguiCameraContext`](crate::EguiCameraContext),
+/// on top of what the camera has rendered.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct EguiCameraTarget;
+
+impl EguiRenderTargetSource for EguiCameraTarget {
+    const PASS_LABEL: &'static str = "egui camera render pass";
+
+    fn pipeline_key(&self, camera: Entity, world: &World) -> Option<EguiPipelineKey> {
+        EguiPipelineKey::from_render_target(
+            world.get::<ExtractedCamera>(camera)?.target.as_ref()?,
+            world.get_resource::<ExtractedWindows>()?,
+            world.get_resource::<RenderAssets<GpuImage>>()?,
+            world.get_resource::<ManualTextureViews>()?,
+        )
+    }
+
+    fn view<'w>(&self, camera: Entity, world: &'w World) -> Option<EguiRenderTargetView<'w>> {
+        let extracted_camera = world.get::<ExtractedCamera>(camera)?;
+        let target = extracted_camera.target.as_ref()?;
+        let images = world.get_resource::<RenderAssets<GpuImage>>()?;
+        let texture_view = target.get_texture_view(
+            world.get_resource::<ExtractedWindows>()?,
+            images,
+            world.get_resource::<ManualTextureViews>()?,
+        )?;
+        let size = extracted_camera.physical_target_size?;
+        let texture = match target {
+            NormalizedRenderTarget::Image(image) => images.get(image).map(|image| &image.texture),
+            NormalizedRenderTarget::Window(_) | NormalizedRenderTarget::TextureView(_) => None,
+        };
+        Some(EguiRenderTargetView {
+            texture_view,
+            size,
+            viewport: camera_viewport(extracted_camera, size),
+            load: LoadOp::Load,
+            texture,
+        })
+    }
+}
+
+/// Egui camera node.
+pub type EguiCameraNode = EguiRenderNode<EguiCameraTarget>;
+
+/// [`RenderLabel`] type for the Egui pass added to a camera render graph,
+/// see [`EguiRenderOrder::InCamera`](crate::EguiRenderOrder::InCamera).
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct EguiViewPass {
+    /// Index of the context entity.
+    pub entity_index: u32,
+    /// Generation of the context entity.
+    pub entity_generation: u32,
+}
+
+/// Renders Egui into the main texture of a camera, from inside the camera's render graph.
+///
+/// Camera contexts are rendered into their viewport, other contexts cover the whole texture.
+#[derive(Clone, Copy, Debug)]
+pub struct EguiViewTarget {
+    /// Camera entity.
+    pub camera: Entity,
+}
+
+impl EguiRenderTargetSource for EguiViewTarget {
+    const PASS_LABEL: &'static str = "egui view render pass";
+
+    fn pipeline_key(&self, _target: Entity, world: &World) -> Option<EguiPipelineKey> {
+        world
+            .get::<ViewTarget>(self.camera)
+            .map(EguiPipelineKey::from_view_target)
+    }
+
+    fn view<'w>(&self, target: Entity, world: &'w World) -> Option<EguiRenderTargetView<'w>> {
+        let view_target = world.get::<ViewTarget>(self.camera)?;
+        let size = world
+            .get::<ExtractedCamera>(self.camera)?
+            .physical_target_size?;
+        let viewport = world
+            .get::<ExtractedCamera>(target)
+            .map_or(URect::from_corners(UVec2::ZERO, size), |extracted_camera| {
+                camera_viewport(extracted_camera, size)
+            });
+        Some(EguiRenderTargetView {
+            texture_view: view_target.main_texture_view(),
+            size,
+            viewport,
+            load: LoadOp::Load,
+            texture: Some(view_target.main_texture()),
+        })
+    }
+
+    fn runs_for_view(&self, view_entity: Option<Entity>) -> bool {
+        view_entity == Some(self.camera)
+    }
+}
+
+/// Egui node added to a camera render graph.
+pub type EguiViewNode = EguiRenderNode<EguiViewTarget>;
+
+fn camera_viewport(extracted_camera: &ExtractedCamera, target_size: UVec2) -> URect {
+    let target_rect = URect::from_corners(UVec2::ZERO, target_size);
+    extracted_camera
+        .viewport
+        .as_ref()
+        .map_or(target_rect, |viewport| {
+            URect::from_corners(
+                viewport.physical_position,
+                viewport.physical_position + viewport.physical_size,
+            )
+            .intersect(target_rect)
+        })
+}
@@ -0,0 +1,94 @@
+#define_import_path bevy_egui::common
+
+// Types, bindings and functions of the Egui shader, custom fragment shaders set with
+// `EguiShader` can import them.
+
+struct Transform {
+    scale: vec2<f32>,
+    translation: vec2<f32>,
+    hdr_brightness: f32,
+    // `EguiShader::params`, unused by the built-in shader.
+    shader_params: vec4<f32>,
+}
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> transform: Transform;
+@group(1) @binding(0) var image_texture: texture_2d<f32>;
+@group(1) @binding(1) var image_sampler: sampler;
+
+fn linear_from_srgb(srgb: vec3<f32>) -> vec3<f32> {
+    let cutoff = srgb < vec3<f32>(0.04045);
+    let lower = srgb / 12.92;
+    let higher = pow((srgb + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(higher, lower, cutoff);
+}
+
+fn srgb_from_linear(linear: vec3<f32>) -> vec3<f32> {
+    let cutoff = linear < vec3<f32>(0.0031308);
+    let lower = linear * 12.92;
+    let higher = 1.055 * pow(linear, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(higher, lower, cutoff);
+}
+
+// Interleaved gradient noise, the same that `egui-wgpu` uses for dithering.
+fn interleaved_gradient_noise(n: vec2<f32>) -> f32 {
+    let f = 0.06711056 * n.x + 0.00583715 * n.y;
+    return fract(52.9829189 * fract(f));
+}
+
+// Dithers a color that is going to be quantized to 8 bits per channel.
+fn dither(rgb: vec3<f32>, frag_coord: vec2<f32>) -> vec3<f32> {
+    let noise = (interleaved_gradient_noise(frag_coord) - 0.5) * 0.95;
+    return clamp(rgb + noise / 255.0, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// Transforms a vertex from Egui points to clip space and converts its color to the space
+// the fragment shader works in.
+fn egui_vertex(in: VertexInput) -> VertexOutput {
+    let position = in.position * transform.scale + transform.translation;
+#ifdef GAMMA_BLENDING
+    // Vertex colors stay in gamma space, as well as the intermediate texture.
+    let color = in.color;
+#else ifdef HDR_TARGET
+    // Colors are premultiplied, so scaling the color channels doesn't affect blending.
+    let color = vec4<f32>(linear_from_srgb(in.color.rgb) * transform.hdr_brightness, in.color.a);
+#else
+    let color = vec4<f32>(linear_from_srgb(in.color.rgb), in.color.a);
+#endif
+    return VertexOutput(vec4<f32>(position, 0.0, 1.0), color, in.uv);
+}
+
+// Returns the premultiplied color of an Egui fragment, ready to be written to the target.
+fn egui_fragment(in: VertexOutput) -> vec4<f32> {
+    let texture_color = textureSample(image_texture, image_sampler, in.uv);
+#ifdef GAMMA_BLENDING
+    // sRGB textures are decoded when sampled.
+    let texture_rgb = srgb_from_linear(texture_color.rgb);
+#else
+    let texture_rgb = texture_color.rgb;
+#endif
+    // This assumes that texture images are not premultiplied.
+    var color = in.color * vec4<f32>(texture_rgb * texture_color.a, texture_color.a);
+
+#ifdef DITHERING
+#ifdef SRGB_TARGET
+    // Banding happens in the encoded values, so the color is dithered in gamma space.
+    color = vec4<f32>(linear_from_srgb(dither(srgb_from_linear(color.rgb), in.position.xy)), color.a);
+#else
+    color = vec4<f32>(dither(color.rgb, in.position.xy), color.a);
+#endif
+#endif
+
+    return color;
+}
@@ -0,0 +1,71 @@
+// Copies a GPU texture registered with `EguiGpuTextures` into a color texture Egui can sample,
+// see `EguiTextureVisualization` for the modes.
+
+struct Params {
+    mode: u32,
+    channel: u32,
+    min: f32,
+    max: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+#ifdef MULTISAMPLED
+#ifdef DEPTH
+@group(0) @binding(1) var source_texture: texture_depth_multisampled_2d;
+#else ifdef UINT
+@group(0) @binding(1) var source_texture: texture_multisampled_2d<u32>;
+#else ifdef SINT
+@group(0) @binding(1) var source_texture: texture_multisampled_2d<i32>;
+#else
+@group(0) @binding(1) var source_texture: texture_multisampled_2d<f32>;
+#endif
+#else
+#ifdef DEPTH
+@group(0) @binding(1) var source_texture: texture_depth_2d;
+#else ifdef UINT
+@group(0) @binding(1) var source_texture: texture_2d<u32>;
+#else ifdef SINT
+@group(0) @binding(1) var source_texture: texture_2d<i32>;
+#else
+@group(0) @binding(1) var source_texture: texture_2d<f32>;
+#endif
+#endif
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // A triangle that covers the whole texture.
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn load(position: vec2<i32>) -> vec4<f32> {
+    // The last argument is the mip level, or the sample index of multisampled textures.
+#ifdef DEPTH
+    let depth = textureLoad(source_texture, position, 0);
+    return vec4<f32>(depth, depth, depth, 1.0);
+#else
+    return vec4<f32>(textureLoad(source_texture, position, 0));
+#endif
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let color = load(vec2<i32>(position.xy));
+    switch params.mode {
+        // Grayscale
+        case 1u: {
+            let value = (color[min(params.channel, 3u)] - params.min) / (params.max - params.min);
+            return vec4<f32>(vec3<f32>(saturate(value)), 1.0);
+        }
+        // Linear depth
+        case 2u: {
+            // Bevy's perspective projections are infinite and reversed, depth is `near / distance`.
+            let distance = params.min / max(color.r, 1e-7);
+            let value = 1.0 - saturate((distance - params.min) / (params.max - params.min));
+            return vec4<f32>(vec3<f32>(value), 1.0);
+        }
+        default: {
+            return color;
+        }
+    }
+}
@@ -1,39 +1,58 @@
 use crate::{
+    backdrop::EguiBackdropEffect,
+    capture::{EguiCapture, EguiCaptureReadback, EguiCaptureReadbacks},
+    paint_callbacks::EguiPaintCallbackRegistry,
     render_systems::{
-        EguiPipelines, EguiTextureBindGroups, EguiTextureId, EguiTransform, EguiTransforms,
+        EguiPipelines, EguiRenderErrors, EguiTextureBindGroups, EguiTextureId, EguiTransform,
+        EguiTransforms, SCRGB_REFERENCE_WHITE_NITS,
     },
-    EguiRenderOutput, EguiSettings, RenderTargetSize,
+    EguiClearColor, EguiError, EguiRenderOutput, EguiSettings, EguiShader, EguiUpscaleFilter,
+    RenderTargetSize,
 };
-use bevy_asset::prelude::*;
+use bevy_asset::{prelude::*, AssetId};
 use bevy_ecs::{
     prelude::*,
     world::{FromWorld, World},
 };
+use bevy_math::{URect, UVec2};
 use bevy_render::{
-    render_asset::RenderAssetUsages,
+    camera::{ManualTextureViews, NormalizedRenderTarget},
+    render_asset::{RenderAssetUsages, RenderAssets},
     render_graph::{Node, NodeRunError, RenderGraphContext},
     render_phase::TrackedRenderPass,
     render_resource::{
-        BindGroupLayout, BindGroupLayoutEntry, BindingType, BlendComponent, BlendFactor,
-        BlendOperation, BlendState, Buffer, BufferAddress, BufferBindingType, BufferDescriptor,
-        BufferUsages, ColorTargetState, ColorWrites, Extent3d, FragmentState, FrontFace,
-        IndexFormat, LoadOp, MultisampleState, Operations, PipelineCache, PrimitiveState,
-        RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor,
-        SamplerBindingType, Shader, ShaderStages, ShaderType, SpecializedRenderPipeline, StoreOp,
-        TextureDimension, TextureFormat, TextureSampleType, TextureViewDimension,
-        VertexBufferLayout, VertexFormat, VertexState, VertexStepMode,
+        BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, BindingResource,
+        BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState, Buffer,
+        BufferAddress, BufferBindingType, BufferDescriptor, BufferUsages, CachedRenderPipelineId,
+        ColorTargetState, ColorWrites, CommandEncoder, Extent3d, FilterMode, FragmentState,
+        FrontFace, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, IndexFormat, LoadOp,
+        MultisampleState, Operations, Origin3d, PipelineCache, PrimitiveState, PrimitiveTopology,
+        RenderPassColorAttachment, RenderPassDepthStencilAttachment, RenderPassDescriptor,
+        RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, Shader,
+        ShaderDefVal, ShaderStages, ShaderType, SpecializedRenderPipeline,
+        SpecializedRenderPipelines, StoreOp, Texture, TextureAspect, TextureDescriptor,
+        TextureDimension, TextureFormat, TextureId, TextureSampleType, TextureUsages, TextureView,
+        TextureViewDescriptor, TextureViewDimension, VertexBufferLayout, VertexFormat, VertexState,
+        VertexStepMode,
     },
-    renderer::{RenderContext, RenderDevice, RenderQueue},
+    renderer::{RenderAdapter, RenderContext, RenderDevice, RenderQueue},
     texture::{
-        GpuImage, Image, ImageAddressMode, ImageFilterMode, ImageSampler, ImageSamplerDescriptor,
+        CachedTexture, GpuImage, Image, ImageAddressMode, ImageFilterMode, ImageSampler,
+        ImageSamplerDescriptor, TextureFormatPixelInfo,
     },
-    view::{ExtractedWindow, ExtractedWindows},
+    view::{ExtractedWindow, ExtractedWindows, ViewTarget},
 };
+use bevy_utils::{Duration, Instant};
 use bytemuck::cast_slice;
 use egui::{TextureFilter, TextureOptions};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Egui shader.
 pub const EGUI_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(9898276442290979394);
+/// Egui shader module imported by the Egui shader and custom fragment shaders, see [`EguiShader`].
+pub const EGUI_COMMON_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(4203471598813296602);
+/// Shader generating mip chains of render targets, see [`EguiMipmapPipeline`].
+pub const EGUI_MIPMAP_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(1130485871650253937);
 
 /// Egui render pipeline.
 #[derive(Resource)]
@@ -52,7 +71,7 @@ impl FromWorld for EguiPipeline {
             "egui transform bind group layout",
             &[BindGroupLayoutEntry {
                 binding: 0,
-                visibility: ShaderStages::VERTEX,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
                 ty: BindingType::Buffer {
                     ty: BufferBindingType::Uniform,
                     has_dynamic_offset: true,
@@ -92,28 +111,218 @@ impl FromWorld for EguiPipeline {
 }
 
 /// Key for specialized pipeline.
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub struct EguiPipelineKey {
     /// Texture format of a window's swap chain to render to.
     pub texture_format: TextureFormat,
+    /// How the render target stores colors, selects the shader variant.
+    pub color_space: EguiTargetColorSpace,
+    /// Blend in gamma space, see [`EguiSettings::gamma_blending`].
+    pub gamma_blending: bool,
+    /// Dither the output, see [`EguiSettings::dithering`].
+    pub dithering: bool,
+    /// Sample count of the color attachment, see [`EguiSettings::msaa_samples`].
+    pub sample_count: u32,
+    /// Filter the output is upscaled with, is set if Egui is rendered at a reduced
+    /// resolution, see [`EguiSettings::render_scale`].
+    pub upscale_filter: Option<EguiUpscaleFilter>,
+    /// Custom fragment shader, see [`EguiShader`].
+    pub fragment_shader: Option<AssetId<Shader>>,
+}
+
+/// How a render target stores colors.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum EguiTargetColorSpace {
+    /// An sRGB format, the GPU encodes the linear colors written by the shader (default).
+    #[default]
+    Srgb,
+    /// A non-sRGB fixed point format, linear colors are stored as is.
+    Linear,
+    /// A float format, linear colors are scaled to match
+    /// [`EguiSettings::hdr_paper_white_nits`](crate::EguiSettings::hdr_paper_white_nits).
+    Hdr,
+}
+
+impl EguiTargetColorSpace {
+    /// Returns the color space of a texture format.
+    pub fn from_texture_format(texture_format: TextureFormat) -> Self {
+        match texture_format {
+            TextureFormat::R16Float
+            | TextureFormat::Rg16Float
+            | TextureFormat::Rgba16Float
+            | TextureFormat::R32Float
+            | TextureFormat::Rg32Float
+            | TextureFormat::Rgba32Float
+            | TextureFormat::Rg11b10Float => Self::Hdr,
+            texture_format if texture_format.is_srgb() => Self::Srgb,
+            _ => Self::Linear,
+        }
+    }
 }
 
 impl EguiPipelineKey {
+    /// Format of the intermediate texture Egui is drawn into when blending in gamma space.
+    pub const GAMMA_BLENDING_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+
+    /// Constructs a pipeline key from the format of a render target.
+    pub fn from_texture_format(texture_format: TextureFormat) -> Self {
+        Self {
+            texture_format,
+            color_space: EguiTargetColorSpace::from_texture_format(texture_format),
+            gamma_blending: false,
+            dithering: false,
+            sample_count: 1,
+            upscale_filter: None,
+            fragment_shader: None,
+        }
+    }
+
+    /// Applies the shader variants and the sample count selected in the context settings.
+    ///
+    /// Sample counts the adapter doesn't support fall back to a supported one, with a warning.
+    pub fn with_settings(self, egui_settings: &EguiSettings, adapter: &RenderAdapter) -> Self {
+        let key = Self {
+            gamma_blending: egui_settings.gamma_blending,
+            dithering: egui_settings.dithering,
+            sample_count: 1,
+            upscale_filter: (egui_settings.clamped_render_scale() != 1.0)
+                .then_some(egui_settings.upscale_filter),
+            ..self
+        };
+        // Paint callbacks can request a depth attachment with the same sample count.
+        let formats = [
+            key.color_attachment_key().texture_format,
+            EguiPaintCallbackTarget::DEPTH_FORMAT,
+        ];
+        let sample_count = supported_sample_count(egui_settings.msaa_samples, |count| {
+            formats.iter().all(|format| {
+                adapter
+                    .get_texture_format_features(*format)
+                    .flags
+                    .sample_count_supported(count)
+            })
+        });
+        Self {
+            sample_count,
+            ..key
+        }
+    }
+
+    /// Applies the custom fragment shader of a context.
+    pub fn with_shader(self, egui_shader: Option<&EguiShader>) -> Self {
+        Self {
+            fragment_shader: egui_shader.map(|egui_shader| egui_shader.shader.id()),
+            ..self
+        }
+    }
+
+    /// Disables the settings that require an intermediate texture, for render targets that
+    /// Egui is always drawn into directly.
+    pub fn without_intermediate_texture(self) -> Self {
+        Self {
+            gamma_blending: false,
+            sample_count: 1,
+            upscale_filter: None,
+            ..self
+        }
+    }
+
+    /// Returns `true` if Egui is drawn into an intermediate texture, which is composited onto
+    /// the render target afterwards.
+    pub fn uses_intermediate_texture(&self) -> bool {
+        self.gamma_blending || self.sample_count > 1 || self.upscale_filter.is_some()
+    }
+
+    /// Returns the key of the texture Egui meshes and paint callbacks are drawn into.
+    ///
+    /// It differs from the render target when blending in gamma space, and its sample count
+    /// is greater than 1 when MSAA is enabled.
+    pub fn color_attachment_key(&self) -> Self {
+        if self.gamma_blending {
+            Self {
+                sample_count: self.sample_count,
+                upscale_filter: self.upscale_filter,
+                ..Self::from_texture_format(Self::GAMMA_BLENDING_TEXTURE_FORMAT)
+            }
+        } else {
+            *self
+        }
+    }
+
     /// Constructs a pipeline key from a window.
     pub fn from_extracted_window(window: &ExtractedWindow) -> Option<Self> {
-        Some(Self {
-            texture_format: window.swap_chain_texture_format?.add_srgb_suffix(),
-        })
+        // Swap chain texture views always have an sRGB format.
+        Some(Self::from_texture_format(
+            window.swap_chain_texture_format?.add_srgb_suffix(),
+        ))
     }
 
     /// Constructs a pipeline key from a gpu image.
     pub fn from_gpu_image(image: &GpuImage) -> Self {
-        EguiPipelineKey {
-            texture_format: image.texture_format.add_srgb_suffix(),
+        Self::from_texture_format(image.texture_format)
+    }
+
+    /// Constructs a pipeline key from the main texture of a camera view.
+    pub fn from_view_target(view_target: &ViewTarget) -> Self {
+        Self::from_texture_format(view_target.main_texture_format())
+    }
+
+    /// Constructs a pipeline key from a camera render target.
+    pub fn from_render_target(
+        target: &NormalizedRenderTarget,
+        windows: &ExtractedWindows,
+        images: &RenderAssets<GpuImage>,
+        manual_texture_views: &ManualTextureViews,
+    ) -> Option<Self> {
+        let texture_format = target.get_texture_format(windows, images, manual_texture_views)?;
+        Some(match target {
+            NormalizedRenderTarget::Window(_) => {
+                Self::from_texture_format(texture_format.add_srgb_suffix())
+            }
+            _ => Self::from_texture_format(texture_format),
+        })
+    }
+
+    /// Returns the shader defs of the pipeline variant.
+    pub fn shader_defs(&self) -> Vec<ShaderDefVal> {
+        let mut shader_defs = Vec::new();
+        if self.gamma_blending {
+            shader_defs.push("GAMMA_BLENDING".into());
+        } else {
+            match self.color_space {
+                EguiTargetColorSpace::Srgb => shader_defs.push("SRGB_TARGET".into()),
+                EguiTargetColorSpace::Linear => {}
+                EguiTargetColorSpace::Hdr => shader_defs.push("HDR_TARGET".into()),
+            }
+        }
+        // Float targets don't suffer from banding.
+        if self.dithering && (self.gamma_blending || self.color_space != EguiTargetColorSpace::Hdr)
+        {
+            shader_defs.push("DITHERING".into());
         }
+        shader_defs
     }
 }
 
+/// Describes the render target a paint callback draws to.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct EguiPaintCallbackTarget {
+    /// Key of the Egui pipeline, contains the format of the color attachment.
+    pub pipeline_key: EguiPipelineKey,
+    /// Sample count of the color attachment.
+    pub sample_count: u32,
+    /// Format of the depth attachment, is set if the callback requested one with
+    /// [`EguiBevyPaintCallbackImpl::depth_attachment`].
+    pub depth_format: Option<TextureFormat>,
+    /// Physical size of the render target.
+    pub size: UVec2,
+}
+
+impl EguiPaintCallbackTarget {
+    /// Format of the depth attachments provided to paint callbacks.
+    pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+}
+
 impl SpecializedRenderPipeline for EguiPipeline {
     type Key = EguiPipelineKey;
 
@@ -126,7 +335,7 @@ impl SpecializedRenderPipeline for EguiPipeline {
             ],
             vertex: VertexState {
                 shader: EGUI_SHADER_HANDLE,
-                shader_defs: Vec::new(),
+                shader_defs: key.shader_defs(),
                 entry_point: "vs_main".into(),
                 buffers: vec![VertexBufferLayout::from_vertex_formats(
                     VertexStepMode::Vertex,
@@ -138,25 +347,13 @@ impl SpecializedRenderPipeline for EguiPipeline {
                 )],
             },
             fragment: Some(FragmentState {
-                shader: EGUI_SHADER_HANDLE,
-                shader_defs: Vec::new(),
+                // The handle is kept alive by the `EguiShader` component in the main world.
+                shader: key.fragment_shader.map_or(EGUI_SHADER_HANDLE, Handle::Weak),
+                shader_defs: key.shader_defs(),
                 entry_point: "fs_main".into(),
-                targets: vec![Some(ColorTargetState {
-                    format: key.texture_format,
-                    blend: Some(BlendState {
-                        color: BlendComponent {
-                            src_factor: BlendFactor::One,
-                            dst_factor: BlendFactor::OneMinusSrcAlpha,
-                            operation: BlendOperation::Add,
-                        },
-                        alpha: BlendComponent {
-                            src_factor: BlendFactor::One,
-                            dst_factor: BlendFactor::OneMinusSrcAlpha,
-                            operation: BlendOperation::Add,
-                        },
-                    }),
-                    write_mask: ColorWrites::ALL,
-                })],
+                targets: vec![Some(premultiplied_alpha_target(
+                    key.color_attachment_key().texture_format,
+                ))],
             }),
             primitive: PrimitiveState {
                 front_face: FrontFace::Cw,
@@ -164,12 +361,223 @@ impl SpecializedRenderPipeline for EguiPipeline {
                 ..Default::default()
             },
             depth_stencil: None,
+            multisample: MultisampleState {
+                count: key.sample_count,
+                ..Default::default()
+            },
+            push_constant_ranges: vec![],
+        }
+    }
+}
+
+/// Composites the intermediate texture of contexts that blend in gamma space, use MSAA or
+/// render at a reduced resolution onto their render targets.
+#[derive(Resource)]
+pub struct EguiCompositePipeline {
+    transform_bind_group_layout: BindGroupLayout,
+    texture_bind_group_layout: BindGroupLayout,
+}
+
+impl FromWorld for EguiCompositePipeline {
+    fn from_world(render_world: &mut World) -> Self {
+        let egui_pipeline = render_world.resource::<EguiPipeline>();
+        Self {
+            transform_bind_group_layout: egui_pipeline.transform_bind_group_layout.clone(),
+            texture_bind_group_layout: egui_pipeline.texture_bind_group_layout.clone(),
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for EguiCompositePipeline {
+    type Key = EguiPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if key.upscale_filter.is_some() {
+            shader_defs.push("UPSCALING".into());
+        }
+        if key.gamma_blending {
+            shader_defs.push("GAMMA_BLENDING".into());
+            if key.color_space == EguiTargetColorSpace::Hdr {
+                shader_defs.push("HDR_TARGET".into());
+            }
+        }
+        RenderPipelineDescriptor {
+            label: Some("egui composite pipeline".into()),
+            layout: vec![
+                self.transform_bind_group_layout.clone(),
+                self.texture_bind_group_layout.clone(),
+            ],
+            vertex: VertexState {
+                shader: EGUI_SHADER_HANDLE,
+                shader_defs: shader_defs.clone(),
+                entry_point: "vs_composite".into(),
+                buffers: Vec::new(),
+            },
+            fragment: Some(FragmentState {
+                shader: EGUI_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "fs_composite".into(),
+                targets: vec![Some(premultiplied_alpha_target(key.texture_format))],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
             multisample: MultisampleState::default(),
             push_constant_ranges: vec![],
         }
     }
 }
 
+/// Draws the [`EguiBackdropEffect`]s of contexts.
+#[derive(Resource)]
+pub struct EguiBackdropPipeline {
+    transform_bind_group_layout: BindGroupLayout,
+    texture_bind_group_layout: BindGroupLayout,
+}
+
+impl FromWorld for EguiBackdropPipeline {
+    fn from_world(render_world: &mut World) -> Self {
+        let egui_pipeline = render_world.resource::<EguiPipeline>();
+        Self {
+            transform_bind_group_layout: egui_pipeline.transform_bind_group_layout.clone(),
+            texture_bind_group_layout: egui_pipeline.texture_bind_group_layout.clone(),
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for EguiBackdropPipeline {
+    type Key = EguiPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let shader_defs = key.shader_defs();
+        RenderPipelineDescriptor {
+            label: Some("egui backdrop pipeline".into()),
+            layout: vec![
+                self.transform_bind_group_layout.clone(),
+                self.texture_bind_group_layout.clone(),
+            ],
+            vertex: VertexState {
+                shader: EGUI_SHADER_HANDLE,
+                shader_defs: shader_defs.clone(),
+                entry_point: "vs_backdrop".into(),
+                buffers: vec![VertexBufferLayout::from_vertex_formats(
+                    VertexStepMode::Instance,
+                    [
+                        VertexFormat::Float32x4, // rect
+                        VertexFormat::Float32x4, // rounding
+                        VertexFormat::Float32x4, // viewport uv
+                        VertexFormat::Float32x2, // blur radius uv
+                    ],
+                )],
+            },
+            fragment: Some(FragmentState {
+                shader: EGUI_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "fs_backdrop".into(),
+                targets: vec![Some(premultiplied_alpha_target(
+                    key.color_attachment_key().texture_format,
+                ))],
+            }),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: key.sample_count,
+                ..Default::default()
+            },
+            push_constant_ranges: vec![],
+        }
+    }
+}
+
+/// Downsamples each mip level of a render target into the next one, for render targets
+/// with a mip chain (see [`EguiRenderToTextureSampling`](crate::EguiRenderToTextureSampling)).
+#[derive(Resource)]
+pub struct EguiMipmapPipeline {
+    texture_bind_group_layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+impl FromWorld for EguiMipmapPipeline {
+    fn from_world(render_world: &mut World) -> Self {
+        let render_device = render_world.resource::<RenderDevice>();
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("egui mipmap sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+        Self {
+            texture_bind_group_layout: render_world
+                .resource::<EguiPipeline>()
+                .texture_bind_group_layout
+                .clone(),
+            sampler,
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for EguiMipmapPipeline {
+    type Key = TextureFormat;
+
+    fn specialize(&self, format: Self::Key) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: Some("egui mipmap pipeline".into()),
+            layout: vec![self.texture_bind_group_layout.clone()],
+            vertex: VertexState {
+                shader: EGUI_MIPMAP_SHADER_HANDLE,
+                shader_defs: Vec::new(),
+                entry_point: "vs_main".into(),
+                buffers: Vec::new(),
+            },
+            fragment: Some(FragmentState {
+                shader: EGUI_MIPMAP_SHADER_HANDLE,
+                shader_defs: Vec::new(),
+                entry_point: "fs_main".into(),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+        }
+    }
+}
+
+/// Views of each mip level of a render target and bind groups sampling them.
+struct MipChain {
+    texture: Texture,
+    level_views: Vec<TextureView>,
+    /// Bind groups of all the levels except the last one, the source of the next level.
+    level_bind_groups: Vec<BindGroup>,
+    pipeline: CachedRenderPipelineId,
+}
+
+fn premultiplied_alpha_target(format: TextureFormat) -> ColorTargetState {
+    ColorTargetState {
+        format,
+        blend: Some(BlendState {
+            color: BlendComponent {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+            alpha: BlendComponent {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+        }),
+        write_mask: ColorWrites::ALL,
+    }
+}
+
 pub(crate) struct DrawCommand {
     pub(crate) clip_rect: egui::Rect,
     pub(crate) primitive: DrawPrimitive,
@@ -178,6 +586,14 @@ pub(crate) struct DrawCommand {
 pub(crate) enum DrawPrimitive {
     Egui(EguiDraw),
     PaintCallback(PaintCallbackDraw),
+    Backdrop(BackdropDraw),
+}
+
+pub(crate) struct BackdropDraw {
+    pub(crate) effect: EguiBackdropEffect,
+    pub(crate) rect: egui::Rect,
+    /// Index of the effect in the backdrop instance buffer.
+    pub(crate) instance: u32,
 }
 
 pub(crate) struct PaintCallbackDraw {
@@ -186,99 +602,290 @@ pub(crate) struct PaintCallbackDraw {
 }
 
 pub(crate) struct EguiDraw {
-    pub(crate) vertices_count: usize,
+    pub(crate) first_index: u32,
+    pub(crate) indices_count: u32,
+    pub(crate) base_vertex: i32,
     pub(crate) egui_texture: EguiTextureId,
 }
 
-/// Egui render node.
-pub struct EguiNode {
-    window_entity: Entity,
+/// Returns the sample count to draw with for the requested one, falls back to `4` or `1` if it
+/// isn't supported.
+fn supported_sample_count(requested: u32, is_supported: impl Fn(u32) -> bool) -> u32 {
+    if requested <= 1 {
+        return 1;
+    }
+    // GPUs support power of two sample counts only.
+    let sample_count = requested.next_power_of_two();
+    if sample_count <= 8 && is_supported(sample_count) {
+        return sample_count;
+    }
+    let fallback = if is_supported(4) { 4 } else { 1 };
+    bevy_log::warn_once!(
+        "Egui MSAA sample count {requested} isn't supported, falling back to {fallback}"
+    );
+    fallback
+}
+
+/// A GPU buffer that grows to fit the written data and shrinks back once it stays oversized
+/// for [`EguiBuffer::SHRINK_DELAY`].
+pub(crate) struct EguiBuffer {
+    label: &'static str,
+    usage: BufferUsages,
+    buffer: Option<Buffer>,
+    capacity: usize,
+    oversized_since: Option<Instant>,
+}
+
+impl EguiBuffer {
+    const MIN_CAPACITY: usize = 4096;
+    const SHRINK_DELAY: Duration = Duration::from_secs(10);
+
+    pub(crate) fn new(label: &'static str, usage: BufferUsages) -> Self {
+        Self {
+            label,
+            usage: usage | BufferUsages::COPY_DST,
+            buffer: None,
+            capacity: 0,
+            oversized_since: None,
+        }
+    }
+
+    pub(crate) fn buffer(&self) -> Option<&Buffer> {
+        self.buffer.as_ref()
+    }
+
+    /// Uploads the data, (re)allocating the buffer if needed.
+    pub(crate) fn write(
+        &mut self,
+        render_device: &RenderDevice,
+        render_queue: &RenderQueue,
+        data: &[u8],
+    ) {
+        if self.update_capacity(data.len(), Instant::now()) {
+            self.buffer = Some(render_device.create_buffer(&BufferDescriptor {
+                label: Some(self.label),
+                size: self.capacity as BufferAddress,
+                usage: self.usage,
+                mapped_at_creation: false,
+            }));
+        }
+
+        if let Some(buffer) = &self.buffer {
+            if !data.is_empty() {
+                render_queue.write_buffer(buffer, 0, data);
+            }
+        }
+    }
+
+    /// Updates the capacity for writing `len` bytes at `now`, returns `true` if the buffer has to
+    /// be reallocated.
+    fn update_capacity(&mut self, len: usize, now: Instant) -> bool {
+        let new_size = if len > self.capacity {
+            len
+        } else if self.capacity > Self::MIN_CAPACITY && len <= self.capacity / 4 {
+            let oversized_since = *self.oversized_since.get_or_insert(now);
+            if now.duration_since(oversized_since) < Self::SHRINK_DELAY {
+                return false;
+            }
+            // Leave some headroom, so that we don't reallocate again right away.
+            len * 2
+        } else {
+            self.oversized_since = None;
+            return false;
+        };
+
+        self.capacity = new_size.next_power_of_two().max(Self::MIN_CAPACITY);
+        self.oversized_since = None;
+        true
+    }
+}
+
+/// A render target an [`EguiRenderNode`] draws to.
+///
+/// Implementing this trait is all that's needed to support a new kind of render targets:
+/// mesh batching, buffer management, paint callbacks and draw calls are shared by all of them.
+pub trait EguiRenderTargetSource: Send + Sync + 'static {
+    /// Label of the render pass.
+    const PASS_LABEL: &'static str;
+
+    /// Returns the key of the pipeline to render with, or `None` if the target isn't available.
+    fn pipeline_key(&self, target: Entity, world: &World) -> Option<EguiPipelineKey>;
+
+    /// Returns the texture view to render to, or `None` if the target isn't available.
+    fn view<'w>(&self, target: Entity, world: &'w World) -> Option<EguiRenderTargetView<'w>>;
+
+    /// Whether the render target keeps its content between frames (`false` by default).
+    ///
+    /// Such targets are redrawn only when the context has new paint jobs (see
+    /// [`EguiSettings::reactive_rendering`]), the target or its pipeline changes, or the context
+    /// uses a custom shader or paint callbacks.
+    const RETAINS_CONTENT: bool = false;
+
+    /// Whether Egui can be drawn into an intermediate texture first (`true` by default), which
+    /// gamma blending, MSAA and upscaling require.
+    const SUPPORTS_INTERMEDIATE_TEXTURE: bool = true;
+
+    /// Returns `false` if the node has to be skipped for the view its graph runs for.
+    ///
+    /// Nodes added to a camera render graph run for every camera that uses the graph.
+    fn runs_for_view(&self, view_entity: Option<Entity>) -> bool {
+        let _ = view_entity;
+        true
+    }
+}
+
+/// Texture view an [`EguiRenderNode`] renders to.
+pub struct EguiRenderTargetView<'w> {
+    /// Texture view of the render target.
+    pub texture_view: &'w TextureView,
+    /// Physical size of the render target.
+    pub size: UVec2,
+    /// Physical rect of the render target Egui is drawn into, equals the whole target
+    /// unless it's rendered into a camera viewport.
+    pub viewport: URect,
+    /// Operation performed on the render target at the start of the render pass.
+    pub load: LoadOp<wgpu_types::Color>,
+    /// Texture of the render target, is set if it can be read by [`EguiBackdropEffect`]s.
+    pub texture: Option<&'w Texture>,
+}
+
+/// Renders Egui to the swap chain texture of a window.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct EguiWindowTarget;
+
+impl EguiRenderTargetSource for EguiWindowTarget {
+    const PASS_LABEL: &'static str = "egui render pass";
+
+    fn pipeline_key(&self, window: Entity, world: &World) -> Option<EguiPipelineKey> {
+        world
+            .get_resource::<ExtractedWindows>()?
+            .windows
+            .get(&window)
+            .and_then(EguiPipelineKey::from_extracted_window)
+    }
+
+    fn view<'w>(&self, window: Entity, world: &'w World) -> Option<EguiRenderTargetView<'w>> {
+        let extracted_window = world
+            .get_resource::<ExtractedWindows>()?
+            .windows
+            .get(&window)?;
+        let size = UVec2::new(
+            extracted_window.physical_width,
+            extracted_window.physical_height,
+        );
+        Some(EguiRenderTargetView {
+            texture_view: extracted_window.swap_chain_texture_view.as_ref()?,
+            size,
+            viewport: URect::from_corners(UVec2::ZERO, size),
+            load: LoadOp::Load,
+            // Swap chain textures can only be rendered to.
+            texture: None,
+        })
+    }
+}
+
+/// Egui render node, draws the output of a context into the render target provided by `S`.
+pub struct EguiRenderNode<S: EguiRenderTargetSource> {
+    source: S,
+    target: Entity,
     vertex_data: Vec<u8>,
-    vertex_buffer_capacity: usize,
-    vertex_buffer: Option<Buffer>,
+    vertex_buffer: EguiBuffer,
     index_data: Vec<u8>,
-    index_buffer_capacity: usize,
-    index_buffer: Option<Buffer>,
+    index_buffer: EguiBuffer,
     draw_commands: Vec<DrawCommand>,
-    postponed_updates: Vec<(egui::Rect, PaintCallbackDraw)>,
+    depth_texture: Option<CachedTexture>,
+    intermediate_texture: Option<(CachedTexture, BindGroup)>,
+    /// Key the intermediate texture bind group was created for, its sampler depends on it.
+    intermediate_texture_key: Option<EguiPipelineKey>,
+    multisampled_texture: Option<CachedTexture>,
+    composite_pipeline: Option<CachedRenderPipelineId>,
+    backdrop_data: Vec<u8>,
+    backdrop_buffer: EguiBuffer,
+    backdrop_texture: Option<(CachedTexture, BindGroup)>,
+    backdrop_pipeline: Option<CachedRenderPipelineId>,
+    mip_chain: Option<MipChain>,
+    /// Pipeline key and texture of the render target the last frame was drawn with.
+    drawn_target: Option<(EguiPipelineKey, Option<TextureId>)>,
+    needs_redraw: AtomicBool,
+    /// Offscreen copy of the render target, exists while a capture is pending.
+    capture_texture: Option<(Texture, TextureView)>,
+    capture_pending: AtomicBool,
     pixels_per_point: f32,
 }
 
-impl EguiNode {
+/// Egui render node for windows.
+pub type EguiNode = EguiRenderNode<EguiWindowTarget>;
+
+impl<S: EguiRenderTargetSource + Default> EguiRenderNode<S> {
     /// Constructs Egui render node.
-    pub fn new(window_entity: Entity) -> Self {
-        EguiNode {
-            window_entity,
+    pub fn new(target: Entity) -> Self {
+        Self::with_source(S::default(), target)
+    }
+}
+
+impl<S: EguiRenderTargetSource> EguiRenderNode<S> {
+    /// Constructs Egui render node with the specified render target source.
+    pub fn with_source(source: S, target: Entity) -> Self {
+        Self {
+            source,
+            target,
             draw_commands: Vec::new(),
             vertex_data: Vec::new(),
-            vertex_buffer_capacity: 0,
-            vertex_buffer: None,
+            vertex_buffer: EguiBuffer::new("egui vertex buffer", BufferUsages::VERTEX),
             index_data: Vec::new(),
-            index_buffer_capacity: 0,
-            index_buffer: None,
-            postponed_updates: Vec::new(),
+            index_buffer: EguiBuffer::new("egui index buffer", BufferUsages::INDEX),
+            depth_texture: None,
+            intermediate_texture: None,
+            intermediate_texture_key: None,
+            multisampled_texture: None,
+            composite_pipeline: None,
+            backdrop_data: Vec::new(),
+            backdrop_buffer: EguiBuffer::new("egui backdrop buffer", BufferUsages::VERTEX),
+            backdrop_texture: None,
+            backdrop_pipeline: None,
+            mip_chain: None,
+            drawn_target: None,
+            needs_redraw: AtomicBool::new(true),
+            capture_texture: None,
+            capture_pending: AtomicBool::new(false),
             pixels_per_point: 1.,
         }
     }
-}
 
-impl Node for EguiNode {
-    fn update(&mut self, world: &mut World) {
-        let Some(key) = world
-            .get_resource::<ExtractedWindows>()
-            .and_then(|windows| windows.windows.get(&self.window_entity))
-            .and_then(EguiPipelineKey::from_extracted_window)
-        else {
-            return;
-        };
-
-        let mut render_target_query =
-            world.query::<(&EguiSettings, &RenderTargetSize, &mut EguiRenderOutput)>();
-
-        let Ok((egui_settings, window_size, mut render_output)) =
-            render_target_query.get_mut(world, self.window_entity)
-        else {
-            return;
-        };
-        let window_size = *window_size;
-        let paint_jobs = std::mem::take(&mut render_output.paint_jobs);
-
-        self.pixels_per_point = window_size.scale_factor * egui_settings.scale_factor;
-        if window_size.physical_width == 0.0 || window_size.physical_height == 0.0 {
-            return;
-        }
-
-        let mut index_offset = 0;
+    /// Returns the entity of the context the node renders.
+    pub fn target(&self) -> Entity {
+        self.target
+    }
 
+    fn prepare_buffers(
+        &mut self,
+        paint_jobs: Vec<egui::ClippedPrimitive>,
+        render_target_size: RenderTargetSize,
+        world: &World,
+    ) {
         self.draw_commands.clear();
         self.vertex_data.clear();
         self.index_data.clear();
-        self.postponed_updates.clear();
+        let mut backdrop_instances = 0;
 
-        let render_device = world.get_resource::<RenderDevice>().unwrap();
+        let (Some(render_device), Some(render_queue)) = (
+            self.render_resource::<RenderDevice>(world),
+            self.render_resource::<RenderQueue>(world),
+        ) else {
+            return;
+        };
 
         for egui::epaint::ClippedPrimitive {
             clip_rect,
             primitive,
         } in paint_jobs
         {
-            let clip_urect = bevy_math::URect {
-                min: bevy_math::UVec2 {
-                    x: (clip_rect.min.x * self.pixels_per_point).round() as u32,
-                    y: (clip_rect.min.y * self.pixels_per_point).round() as u32,
-                },
-                max: bevy_math::UVec2 {
-                    x: (clip_rect.max.x * self.pixels_per_point).round() as u32,
-                    y: (clip_rect.max.y * self.pixels_per_point).round() as u32,
-                },
-            };
+            let clip_urect = clip_rect_in_pixels(clip_rect, self.pixels_per_point);
 
             if clip_urect
-                .intersect(bevy_math::URect::new(
-                    0,
-                    0,
-                    window_size.physical_width as u32,
-                    window_size.physical_height as u32,
+                .intersect(URect::from_corners(
+                    UVec2::ZERO,
+                    physical_size(&render_target_size),
                 ))
                 .is_empty()
             {
@@ -288,203 +895,774 @@ impl Node for EguiNode {
             let mesh = match primitive {
                 egui::epaint::Primitive::Mesh(mesh) => mesh,
                 egui::epaint::Primitive::Callback(paint_callback) => {
-                    let Ok(callback) = paint_callback.callback.downcast::<EguiBevyPaintCallback>()
-                    else {
-                        unimplemented!("Unsupported egui paint callback type");
+                    if let Some(effect) =
+                        paint_callback.callback.downcast_ref::<EguiBackdropEffect>()
+                    {
+                        self.draw_commands.push(DrawCommand {
+                            primitive: DrawPrimitive::Backdrop(BackdropDraw {
+                                effect: *effect,
+                                rect: paint_callback.rect,
+                                instance: backdrop_instances,
+                            }),
+                            clip_rect,
+                        });
+                        backdrop_instances += 1;
+                        continue;
+                    }
+                    let callback = match world.get_resource::<EguiPaintCallbackRegistry>() {
+                        Some(registry) => registry.adapt(paint_callback.callback),
+                        None => paint_callback.callback.downcast().ok(),
                     };
+                    let Some(callback) = callback else {
+                        self.report_error(world, EguiError::UnsupportedPaintCallback(self.target));
+                        continue;
+                    };
+
+                    self.draw_commands.push(DrawCommand {
+                        primitive: DrawPrimitive::PaintCallback(PaintCallbackDraw {
+                            callback,
+                            rect: paint_callback.rect,
+                        }),
+                        clip_rect,
+                    });
+                    continue;
+                }
+            };
+
+            // Indices are uploaded as is, the draw call offsets them with `base_vertex` instead.
+            let base_vertex = (self.vertex_data.len() / size_of::<egui::epaint::Vertex>()) as i32;
+            let first_index = (self.index_data.len() / size_of::<u32>()) as u32;
+            self.vertex_data
+                .extend_from_slice(cast_slice::<_, u8>(mesh.vertices.as_slice()));
+            self.index_data
+                .extend_from_slice(cast_slice::<_, u8>(mesh.indices.as_slice()));
+
+            let texture_handle = match mesh.texture_id {
+                egui::TextureId::Managed(id) => EguiTextureId::Managed(self.target, id),
+                egui::TextureId::User(id) => EguiTextureId::User(id),
+            };
+
+            self.draw_commands.push(DrawCommand {
+                primitive: DrawPrimitive::Egui(EguiDraw {
+                    first_index,
+                    indices_count: mesh.indices.len() as u32,
+                    base_vertex,
+                    egui_texture: texture_handle,
+                }),
+                clip_rect,
+            });
+        }
+
+        self.vertex_buffer
+            .write(render_device, render_queue, &self.vertex_data);
+        self.index_buffer
+            .write(render_device, render_queue, &self.index_data);
+    }
+
+    /// Keeps the depth texture for the paint callbacks that requested one in sync with
+    /// the render target size, and frees it once no callbacks need it.
+    fn prepare_depth_texture(&mut self, size: UVec2, sample_count: u32, world: &World) {
+        let needs_depth = self.draw_commands.iter().any(|draw_command| {
+            matches!(
+                &draw_command.primitive,
+                DrawPrimitive::PaintCallback(command) if command.callback.cb().depth_attachment()
+            )
+        });
+        if !needs_depth {
+            self.depth_texture = None;
+            return;
+        }
+        if self.depth_texture.as_ref().is_some_and(|depth_texture| {
+            texture_size(depth_texture) == size
+                && depth_texture.texture.sample_count() == sample_count
+        }) {
+            return;
+        }
+        let Some(render_device) = self.render_resource::<RenderDevice>(world) else {
+            return;
+        };
+
+        self.depth_texture = Some(create_attachment_texture(
+            render_device,
+            "egui paint callback depth texture",
+            size,
+            EguiPaintCallbackTarget::DEPTH_FORMAT,
+            sample_count,
+            TextureUsages::RENDER_ATTACHMENT,
+        ));
+    }
+
+    /// Keeps the intermediate textures of contexts that blend in gamma space or use MSAA
+    /// in sync with the render target size.
+    fn prepare_intermediate_textures(
+        &mut self,
+        key: EguiPipelineKey,
+        size: UVec2,
+        world: &mut World,
+    ) {
+        if !key.uses_intermediate_texture() {
+            self.intermediate_texture = None;
+            self.intermediate_texture_key = None;
+            self.multisampled_texture = None;
+            self.composite_pipeline = None;
+            return;
+        }
+        self.composite_pipeline = world
+            .get_resource::<EguiCompositePipeline>()
+            .is_some()
+            .then(|| {
+                world.resource_scope(
+                |world, mut pipelines: Mut<SpecializedRenderPipelines<EguiCompositePipeline>>| {
+                    pipelines.specialize(
+                        world.resource::<PipelineCache>(),
+                        world.resource::<EguiCompositePipeline>(),
+                        key,
+                    )
+                },
+            )
+            });
+
+        let (Some(render_device), Some(egui_pipeline)) = (
+            self.render_resource::<RenderDevice>(world),
+            self.render_resource::<EguiPipeline>(world),
+        ) else {
+            return;
+        };
+        let color_attachment_key = key.color_attachment_key();
+        let is_up_to_date = |texture: &CachedTexture, sample_count: u32| {
+            texture_size(texture) == size
+                && texture.texture.format() == color_attachment_key.texture_format
+                && texture.texture.sample_count() == sample_count
+        };
+
+        if key.sample_count == 1 {
+            self.multisampled_texture = None;
+        } else if !self
+            .multisampled_texture
+            .as_ref()
+            .is_some_and(|texture| is_up_to_date(texture, key.sample_count))
+        {
+            self.multisampled_texture = Some(create_attachment_texture(
+                render_device,
+                "egui multisampled texture",
+                size,
+                color_attachment_key.texture_format,
+                key.sample_count,
+                TextureUsages::RENDER_ATTACHMENT,
+            ));
+        }
+
+        if self.intermediate_texture_key == Some(key)
+            && self
+                .intermediate_texture
+                .as_ref()
+                .is_some_and(|(texture, _)| is_up_to_date(texture, 1))
+        {
+            return;
+        }
+        let texture = create_attachment_texture(
+            render_device,
+            "egui intermediate texture",
+            size,
+            color_attachment_key.texture_format,
+            1,
+            TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+        );
+        // Unless upscaling, the composite shader loads texels directly and the sampler is there
+        // to satisfy the layout.
+        let filter = match key.upscale_filter {
+            Some(EguiUpscaleFilter::Linear) => FilterMode::Linear,
+            Some(EguiUpscaleFilter::Nearest) | None => FilterMode::Nearest,
+        };
+        let bind_group = create_texture_bind_group(
+            render_device,
+            egui_pipeline,
+            "egui intermediate texture bind group",
+            &texture,
+            filter,
+        );
+        self.intermediate_texture = Some((texture, bind_group));
+        self.intermediate_texture_key = Some(key);
+    }
+
+    /// Keeps the backdrop texture in sync with the render target and uploads the instances
+    /// of the [`EguiBackdropEffect`]s to draw.
+    fn prepare_backdrop(
+        &mut self,
+        key: EguiPipelineKey,
+        render_target_size: RenderTargetSize,
+        egui_scale_factor: f32,
+        world: &mut World,
+    ) {
+        self.backdrop_data.clear();
+        let has_backdrop = self
+            .draw_commands
+            .iter()
+            .any(|draw_command| matches!(draw_command.primitive, DrawPrimitive::Backdrop(_)));
+        if !has_backdrop {
+            self.backdrop_texture = None;
+            self.backdrop_pipeline = None;
+            return;
+        }
+        self.backdrop_pipeline =
+            world
+                .get_resource::<EguiBackdropPipeline>()
+                .is_some()
+                .then(|| {
+                    world.resource_scope(
+                |world, mut pipelines: Mut<SpecializedRenderPipelines<EguiBackdropPipeline>>| {
+                    pipelines.specialize(
+                        world.resource::<PipelineCache>(),
+                        world.resource::<EguiBackdropPipeline>(),
+                        key,
+                    )
+                },
+            )
+                });
+
+        let (Some(render_device), Some(render_queue), Some(egui_pipeline)) = (
+            self.render_resource::<RenderDevice>(world),
+            self.render_resource::<RenderQueue>(world),
+            self.render_resource::<EguiPipeline>(world),
+        ) else {
+            return;
+        };
+        let Some(target_view) = self.source.view(self.target, world) else {
+            return;
+        };
+        let Some(texture) = target_view
+            .texture
+            .filter(|texture| texture.usage().contains(TextureUsages::COPY_SRC))
+        else {
+            bevy_utils::warn_once!(
+                "The render target of the Egui context {:?} can't be read, backdrop effects are skipped",
+                self.target
+            );
+            self.backdrop_texture = None;
+            return;
+        };
+
+        let size = UVec2::new(texture.width(), texture.height());
+        if !self
+            .backdrop_texture
+            .as_ref()
+            .is_some_and(|(backdrop_texture, _)| {
+                texture_size(backdrop_texture) == size
+                    && backdrop_texture.texture.format() == texture.format()
+            })
+        {
+            let backdrop_texture = create_attachment_texture(
+                render_device,
+                "egui backdrop texture",
+                size,
+                texture.format(),
+                1,
+                TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            );
+            let bind_group = create_texture_bind_group(
+                render_device,
+                egui_pipeline,
+                "egui backdrop texture bind group",
+                &backdrop_texture,
+                FilterMode::Linear,
+            );
+            self.backdrop_texture = Some((backdrop_texture, bind_group));
+        }
+
+        // Effects are positioned in points like Egui shapes, and sample the part of the target
+        // texture Egui is drawn into.
+        let size = size.as_vec2();
+        let viewport = target_view.viewport;
+        let viewport_uv = [
+            viewport.min.x as f32 / size.x,
+            viewport.min.y as f32 / size.y,
+            viewport.width() as f32 / size.x,
+            viewport.height() as f32 / size.y,
+        ];
+        let target_pixels_per_point =
+            viewport.width() as f32 * egui_scale_factor / render_target_size.width();
+        for draw_command in &self.draw_commands {
+            let DrawPrimitive::Backdrop(BackdropDraw { effect, rect, .. }) =
+                &draw_command.primitive
+            else {
+                continue;
+            };
+            let blur_radius = effect.blur_radius.max(0.0) * target_pixels_per_point;
+            let rounding = effect.rounding;
+            let instance: [f32; 14] = [
+                rect.min.x,
+                rect.min.y,
+                rect.max.x,
+                rect.max.y,
+                rounding.nw,
+                rounding.ne,
+                rounding.sw,
+                rounding.se,
+                viewport_uv[0],
+                viewport_uv[1],
+                viewport_uv[2],
+                viewport_uv[3],
+                blur_radius / size.x,
+                blur_radius / size.y,
+            ];
+            self.backdrop_data
+                .extend_from_slice(cast_slice::<_, u8>(&instance));
+        }
+        self.backdrop_buffer
+            .write(render_device, render_queue, &self.backdrop_data);
+    }
+
+    /// Returns the key of the pipeline, with the shader variants selected in the context settings.
+    fn pipeline_key(&self, world: &World) -> Option<EguiPipelineKey> {
+        let key = self.source.pipeline_key(self.target, world)?;
+        let key = match world.get::<EguiSettings>(self.target) {
+            Some(egui_settings) => {
+                key.with_settings(egui_settings, self.render_resource::<RenderAdapter>(world)?)
+            }
+            None => key,
+        };
+        let key = match S::SUPPORTS_INTERMEDIATE_TEXTURE {
+            true => key,
+            false => key.without_intermediate_texture(),
+        };
+        Some(key.with_shader(world.get::<EguiShader>(self.target)))
+    }
+
+    /// Returns the load operation of the render target, taking [`EguiClearColor`] into account.
+    fn target_load(
+        &self,
+        key: EguiPipelineKey,
+        target_view: &EguiRenderTargetView,
+        world: &World,
+    ) -> LoadOp<wgpu_types::Color> {
+        // The capture texture is always cleared, it would keep the previous capture otherwise.
+        let is_capture = self
+            .capture_texture
+            .as_ref()
+            .is_some_and(|(_, view)| view.id() == target_view.texture_view.id());
+        let color = match world.get::<EguiClearColor>(self.target) {
+            None => return target_view.load,
+            Some(EguiClearColor::None) if is_capture => return target_view.load,
+            Some(EguiClearColor::None) => return LoadOp::Load,
+            Some(EguiClearColor::Transparent) => {
+                return LoadOp::Clear(wgpu_types::Color::TRANSPARENT)
+            }
+            Some(EguiClearColor::Color(color)) => color,
+        };
+        // Render targets receive linear colors, the same as the Egui shader outputs.
+        let color = egui::Rgba::from(*color);
+        let brightness = match (key.color_space, world.get::<EguiSettings>(self.target)) {
+            (EguiTargetColorSpace::Hdr, Some(egui_settings)) => {
+                egui_settings.hdr_paper_white_nits / SCRGB_REFERENCE_WHITE_NITS
+            }
+            _ => 1.0,
+        };
+        LoadOp::Clear(wgpu_types::Color {
+            r: (color.r() * brightness) as f64,
+            g: (color.g() * brightness) as f64,
+            b: (color.b() * brightness) as f64,
+            a: color.a() as f64,
+        })
+    }
+
+    fn paint_callback_target(
+        &self,
+        pipeline_key: EguiPipelineKey,
+        size: UVec2,
+        callback: &dyn EguiBevyPaintCallbackImpl,
+    ) -> EguiPaintCallbackTarget {
+        EguiPaintCallbackTarget {
+            pipeline_key: pipeline_key.color_attachment_key(),
+            sample_count: pipeline_key.sample_count,
+            depth_format: callback
+                .depth_attachment()
+                .then_some(EguiPaintCallbackTarget::DEPTH_FORMAT),
+            size,
+        }
+    }
+
+    /// Returns a render world resource, reporting an error if it's missing.
+    fn render_resource<'w, R: Resource>(&self, world: &'w World) -> Option<&'w R> {
+        let resource = world.get_resource::<R>();
+        if resource.is_none() {
+            self.report_error(
+                world,
+                EguiError::MissingRenderResource {
+                    target: self.target,
+                    resource: std::any::type_name::<R>(),
+                },
+            );
+        }
+        resource
+    }
 
-                    self.postponed_updates.push((
-                        clip_rect,
-                        PaintCallbackDraw {
-                            callback: callback.clone(),
-                            rect: paint_callback.rect,
-                        },
-                    ));
+    fn report_error(&self, world: &World, error: EguiError) {
+        match world.get_resource::<EguiRenderErrors>() {
+            Some(render_errors) => render_errors.push(error),
+            None => bevy_log::error!("{error}"),
+        }
+    }
+}
 
-                    self.draw_commands.push(DrawCommand {
-                        primitive: DrawPrimitive::PaintCallback(PaintCallbackDraw {
-                            callback,
-                            rect: paint_callback.rect,
-                        }),
-                        clip_rect,
-                    });
-                    continue;
-                }
-            };
+impl<S: EguiRenderTargetSource> Node for EguiRenderNode<S> {
+    fn update(&mut self, world: &mut World) {
+        // Capture requests are extracted once, they are kept until the capture succeeds.
+        if world.get::<EguiCapture>(self.target).is_some() {
+            *self.capture_pending.get_mut() = true;
+        }
+        let Some(key) = self.pipeline_key(world) else {
+            return;
+        };
 
-            self.vertex_data
-                .extend_from_slice(cast_slice::<_, u8>(mesh.vertices.as_slice()));
-            let indices_with_offset = mesh
-                .indices
-                .iter()
-                .map(|i| i + index_offset)
-                .collect::<Vec<_>>();
-            self.index_data
-                .extend_from_slice(cast_slice(indices_with_offset.as_slice()));
-            index_offset += mesh.vertices.len() as u32;
+        let mut render_target_query = world.query::<(
+            &EguiSettings,
+            &RenderTargetSize,
+            Option<&mut EguiRenderOutput>,
+        )>();
+        let Ok((egui_settings, render_target_size, render_output)) =
+            render_target_query.get_mut(world, self.target)
+        else {
+            return;
+        };
 
-            let texture_handle = match mesh.texture_id {
-                egui::TextureId::Managed(id) => EguiTextureId::Managed(self.window_entity, id),
-                egui::TextureId::User(id) => EguiTextureId::User(id),
-            };
+        let render_target_size = *render_target_size;
+        // `EguiRenderOutput` is extracted only if it has changed, otherwise we can reuse
+        // the draw commands and buffers from the previous frame.
+        let paint_jobs =
+            render_output.map(|mut render_output| std::mem::take(&mut render_output.paint_jobs));
+        let has_new_paint_jobs = paint_jobs.is_some();
 
-            self.draw_commands.push(DrawCommand {
-                primitive: DrawPrimitive::Egui(EguiDraw {
-                    vertices_count: mesh.indices.len(),
-                    egui_texture: texture_handle,
-                }),
-                clip_rect,
-            });
+        let egui_scale_factor = egui_settings.scale_factor;
+        self.pixels_per_point = render_target_size.scale_factor * egui_scale_factor;
+        if render_target_size.physical_width == 0.0 || render_target_size.physical_height == 0.0 {
+            return;
         }
 
-        if self.vertex_data.len() > self.vertex_buffer_capacity {
-            self.vertex_buffer_capacity = if self.vertex_data.len().is_power_of_two() {
-                self.vertex_data.len()
-            } else {
-                self.vertex_data.len().next_power_of_two()
-            };
-            self.vertex_buffer = Some(render_device.create_buffer(&BufferDescriptor {
-                label: Some("egui vertex buffer"),
-                size: self.vertex_buffer_capacity as BufferAddress,
-                usage: BufferUsages::COPY_DST | BufferUsages::VERTEX,
-                mapped_at_creation: false,
-            }));
+        if let Some(paint_jobs) = paint_jobs {
+            self.prepare_buffers(paint_jobs, render_target_size, world);
         }
-        if self.index_data.len() > self.index_buffer_capacity {
-            self.index_buffer_capacity = if self.index_data.len().is_power_of_two() {
-                self.index_data.len()
-            } else {
-                self.index_data.len().next_power_of_two()
-            };
-            self.index_buffer = Some(render_device.create_buffer(&BufferDescriptor {
-                label: Some("egui index buffer"),
-                size: self.index_buffer_capacity as BufferAddress,
-                usage: BufferUsages::COPY_DST | BufferUsages::INDEX,
-                mapped_at_creation: false,
-            }));
+
+        // Custom shaders and paint callbacks may change their output every frame.
+        let is_animated = world.get::<EguiShader>(self.target).is_some()
+            || self.draw_commands.iter().any(|draw_command| {
+                matches!(draw_command.primitive, DrawPrimitive::PaintCallback(_))
+            });
+        let drawn_target = (
+            key,
+            self.source
+                .view(self.target, world)
+                .and_then(|view| view.texture.map(Texture::id)),
+        );
+        if has_new_paint_jobs || is_animated || self.drawn_target != Some(drawn_target) {
+            *self.needs_redraw.get_mut() = true;
         }
+        self.drawn_target = Some(drawn_target);
+
+        let viewport_size = physical_size(&render_target_size);
+        // Paint callback targets and depth textures match the whole texture, which may be
+        // larger than the viewport Egui is drawn into, or the intermediate texture when
+        // rendering at a reduced resolution.
+        let size = match key.upscale_filter {
+            Some(_) => viewport_size,
+            None => self
+                .source
+                .view(self.target, world)
+                .map_or(viewport_size, |view| view.size),
+        };
+        self.prepare_depth_texture(size, key.sample_count, world);
+        self.prepare_intermediate_textures(key, size, world);
+        self.prepare_backdrop(key, render_target_size, egui_scale_factor, world);
+        self.prepare_mip_chain(world);
+        self.prepare_capture_texture(key, world);
 
-        for (clip_rect, command) in self.postponed_updates.drain(..) {
+        for draw_command in &self.draw_commands {
+            let DrawPrimitive::PaintCallback(command) = &draw_command.primitive else {
+                continue;
+            };
             let info = egui::PaintCallbackInfo {
                 viewport: command.rect,
-                clip_rect,
+                clip_rect: draw_command.clip_rect,
                 pixels_per_point: self.pixels_per_point,
-                screen_size_px: [
-                    window_size.physical_width as u32,
-                    window_size.physical_height as u32,
-                ],
+                screen_size_px: viewport_size.to_array(),
             };
-            command
-                .callback
-                .cb()
-                .update(info, self.window_entity, key, world);
+            let callback = command.callback.cb();
+            let callback_target = self.paint_callback_target(key, size, callback);
+            callback.update(info, self.target, &callback_target, world);
         }
     }
 
     fn run<'w>(
         &self,
-        _graph: &mut RenderGraphContext,
+        graph: &mut RenderGraphContext,
         render_context: &mut RenderContext<'w>,
         world: &'w World,
     ) -> Result<(), NodeRunError> {
-        let egui_pipelines = &world.get_resource::<EguiPipelines>().unwrap().0;
-        let pipeline_cache = world.get_resource::<PipelineCache>().unwrap();
-
-        let extracted_windows = &world.get_resource::<ExtractedWindows>().unwrap().windows;
-        let extracted_window = extracted_windows.get(&self.window_entity);
-        let swap_chain_texture_view =
-            match extracted_window.and_then(|v| v.swap_chain_texture_view.as_ref()) {
-                None => return Ok(()),
-                Some(window) => window,
-            };
+        if !self.source.runs_for_view(graph.get_view_entity()) {
+            return Ok(());
+        }
+        // Render targets retaining their content are redrawn only when something has changed.
+        let needs_redraw = !S::RETAINS_CONTENT || self.needs_redraw.load(Ordering::Relaxed);
+        if needs_redraw && self.draw(render_context, world) {
+            self.needs_redraw.store(false, Ordering::Relaxed);
+            self.generate_mipmaps(render_context, world);
+        }
+        if self.capture_pending.load(Ordering::Relaxed) && self.capture(render_context, world) {
+            self.capture_pending.store(false, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
 
-        let render_queue = world.get_resource::<RenderQueue>().unwrap();
+impl<S: EguiRenderTargetSource> EguiRenderNode<S> {
+    /// Draws Egui into the render target, returns `false` if it couldn't be drawn completely.
+    fn draw<'w>(&self, render_context: &mut RenderContext<'w>, world: &'w World) -> bool {
+        let Some(mut target_view) = self.source.view(self.target, world) else {
+            return false;
+        };
+        // Only a single mip level can be rendered to, the others are generated afterwards.
+        if let Some(mip_chain) = self.mip_chain(&target_view) {
+            target_view.texture_view = &mip_chain.level_views[0];
+        }
+        self.draw_into(target_view, render_context, world)
+    }
 
-        let (vertex_buffer, index_buffer) = match (&self.vertex_buffer, &self.index_buffer) {
-            (Some(vertex), Some(index)) => (vertex, index),
-            _ => return Ok(()),
+    /// Draws Egui into the capture texture and queues reading it back, returns `false` if it
+    /// couldn't be drawn completely.
+    fn capture<'w>(&self, render_context: &mut RenderContext<'w>, world: &'w World) -> bool {
+        let (Some((texture, texture_view)), Some(target_view), Some(readbacks)) = (
+            self.capture_texture.as_ref(),
+            self.source.view(self.target, world),
+            world.get_resource::<EguiCaptureReadbacks>(),
+        ) else {
+            return false;
         };
+        let viewport = target_view.viewport;
+        // The capture texture lags behind the render target for a frame when it gets resized.
+        if UVec2::new(texture.width(), texture.height()) != target_view.size || viewport.is_empty()
+        {
+            return false;
+        }
+        let capture_view = EguiRenderTargetView {
+            texture_view,
+            size: target_view.size,
+            viewport,
+            load: LoadOp::Clear(wgpu_types::Color::TRANSPARENT),
+            texture: Some(texture),
+        };
+        if !self.draw_into(capture_view, render_context, world) {
+            return false;
+        }
 
-        render_queue.write_buffer(vertex_buffer, 0, &self.vertex_data);
-        render_queue.write_buffer(index_buffer, 0, &self.index_data);
+        let format = texture.format();
+        let padded_bytes_per_row =
+            RenderDevice::align_copy_bytes_per_row(viewport.width() as usize * format.pixel_size())
+                as u32;
+        let buffer = render_context
+            .render_device()
+            .create_buffer(&BufferDescriptor {
+                label: Some("egui capture buffer"),
+                size: padded_bytes_per_row as u64 * viewport.height() as u64,
+                usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+        render_context.command_encoder().copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: Origin3d {
+                    x: viewport.min.x,
+                    y: viewport.min.y,
+                    z: 0,
+                },
+                aspect: TextureAspect::All,
+            },
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d {
+                width: viewport.width(),
+                height: viewport.height(),
+                depth_or_array_layers: 1,
+            },
+        );
+        readbacks.push(EguiCaptureReadback {
+            context: self.target,
+            buffer,
+            size: viewport.size(),
+            padded_bytes_per_row,
+            format,
+        });
+        true
+    }
 
-        let (physical_width, physical_height, pipeline_key) = match extracted_window {
-            Some(window) => (
-                window.physical_width,
-                window.physical_height,
-                EguiPipelineKey::from_extracted_window(window),
-            ),
-            None => unreachable!(),
+    /// Draws Egui into the texture view, returns `false` if it couldn't be drawn completely.
+    fn draw_into<'w>(
+        &self,
+        target_view: EguiRenderTargetView,
+        render_context: &mut RenderContext<'w>,
+        world: &'w World,
+    ) -> bool {
+        let Some(key) = self.pipeline_key(world) else {
+            return false;
         };
-        let Some(key) = pipeline_key else {
-            return Ok(());
+        let viewport = target_view.viewport;
+        let target_load = self.target_load(key, &target_view, world);
+        // When rendering at a reduced resolution, Egui covers the whole intermediate texture,
+        // which is upscaled into the viewport by the composite pass.
+        let (target_size, draw_viewport) = match key.upscale_filter {
+            Some(_) => {
+                let Some(render_target_size) = world.get::<RenderTargetSize>(self.target) else {
+                    return false;
+                };
+                let size = physical_size(render_target_size);
+                (size, URect::from_corners(UVec2::ZERO, size))
+            }
+            None => (target_view.size, viewport),
+        };
+
+        // When blending in gamma space or using MSAA, everything is drawn into an intermediate
+        // texture first. It may lag behind the render target for a frame when it gets resized.
+        let intermediate_texture = self
+            .intermediate_texture
+            .as_ref()
+            .filter(|(texture, _)| texture_size(texture) == target_size);
+        let multisampled_texture = self
+            .multisampled_texture
+            .as_ref()
+            .filter(|texture| texture_size(texture) == target_size);
+        let (color_view, resolve_target, color_load) = if key.uses_intermediate_texture() {
+            let transparent = LoadOp::Clear(wgpu_types::Color::TRANSPARENT);
+            match (intermediate_texture, multisampled_texture) {
+                (Some((texture, _)), _) if key.sample_count == 1 => {
+                    (&texture.default_view, None, transparent)
+                }
+                (Some((texture, _)), Some(multisampled_texture)) => (
+                    &multisampled_texture.default_view,
+                    Some(&texture.default_view),
+                    transparent,
+                ),
+                _ => return false,
+            }
+        } else {
+            (target_view.texture_view, None, target_load)
         };
 
+        // The buffers don't exist until the context draws any meshes. The pass still runs
+        // without them, so that the target gets cleared and paint callbacks are drawn.
+        let mesh_buffers = self.vertex_buffer.buffer().zip(self.index_buffer.buffer());
+
         for draw_command in &self.draw_commands {
             match &draw_command.primitive {
-                DrawPrimitive::Egui(_command) => {}
+                DrawPrimitive::Egui(_) | DrawPrimitive::Backdrop(_) => {}
                 DrawPrimitive::PaintCallback(command) => {
                     let info = egui::PaintCallbackInfo {
                         viewport: command.rect,
                         clip_rect: draw_command.clip_rect,
                         pixels_per_point: self.pixels_per_point,
-                        screen_size_px: [physical_width, physical_height],
+                        screen_size_px: draw_viewport.size().to_array(),
                     };
 
-                    command.callback.cb().prepare_render(
+                    let callback = command.callback.cb();
+                    let callback_target = self.paint_callback_target(key, target_size, callback);
+                    callback.prepare_render(
                         info,
                         render_context,
-                        self.window_entity,
-                        key,
+                        self.target,
+                        &callback_target,
                         world,
                     );
                 }
             }
         }
 
-        let bind_groups = &world.get_resource::<EguiTextureBindGroups>().unwrap();
-
-        let egui_transforms = world.get_resource::<EguiTransforms>().unwrap();
-
-        let device = world.get_resource::<RenderDevice>().unwrap();
+        let (
+            Some(EguiPipelines(egui_pipelines)),
+            Some(pipeline_cache),
+            Some(bind_groups),
+            Some(egui_transforms),
+            Some(device),
+        ) = (
+            self.render_resource::<EguiPipelines>(world),
+            self.render_resource::<PipelineCache>(world),
+            self.render_resource::<EguiTextureBindGroups>(world),
+            self.render_resource::<EguiTransforms>(world),
+            self.render_resource::<RenderDevice>(world),
+        )
+        else {
+            return false;
+        };
+        let (Some(&transform_buffer_offset), Some((_, transform_buffer_bind_group))) = (
+            egui_transforms.offsets.get(&self.target),
+            egui_transforms.bind_group.as_ref(),
+        ) else {
+            self.report_error(
+                world,
+                EguiError::MissingRenderResource {
+                    target: self.target,
+                    resource: std::any::type_name::<EguiTransforms>(),
+                },
+            );
+            return false;
+        };
 
-        let render_pass =
-            render_context
-                .command_encoder()
-                .begin_render_pass(&RenderPassDescriptor {
-                    label: Some("egui render pass"),
-                    color_attachments: &[Some(RenderPassColorAttachment {
-                        view: swap_chain_texture_view,
-                        resolve_target: None,
-                        ops: Operations {
-                            load: LoadOp::Load,
-                            store: StoreOp::Store,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                    timestamp_writes: None,
-                    occlusion_query_set: None,
-                });
-        let mut render_pass = TrackedRenderPass::new(device, render_pass);
+        // Backdrop effects sample what the render target contained before the Egui pass.
+        let backdrop = self
+            .backdrop_texture
+            .as_ref()
+            .zip(target_view.texture)
+            .filter(|((backdrop_texture, _), texture)| {
+                backdrop_texture.texture.size() == texture.size()
+                    && backdrop_texture.texture.format() == texture.format()
+            });
+        if let Some(((backdrop_texture, _), texture)) = backdrop {
+            render_context.command_encoder().copy_texture_to_texture(
+                texture.as_image_copy(),
+                backdrop_texture.texture.as_image_copy(),
+                texture.size(),
+            );
+        }
+        let backdrop_pipeline = self
+            .backdrop_pipeline
+            .and_then(|pipeline_id| pipeline_cache.get_render_pipeline(pipeline_id));
+
+        let mut render_pass = begin_render_pass(
+            render_context.command_encoder(),
+            device,
+            S::PASS_LABEL,
+            color_view,
+            resolve_target,
+            color_load,
+            None,
+        );
 
-        let pipeline_id = egui_pipelines.get(&self.window_entity).unwrap();
+        let Some(pipeline_id) = egui_pipelines.get(&self.target) else {
+            self.report_error(
+                world,
+                EguiError::MissingRenderResource {
+                    target: self.target,
+                    resource: std::any::type_name::<EguiPipelines>(),
+                },
+            );
+            return false;
+        };
         let Some(pipeline) = pipeline_cache.get_render_pipeline(*pipeline_id) else {
-            return Ok(());
+            return false;
         };
 
-        let transform_buffer_offset = egui_transforms.offsets[&self.window_entity];
-        let transform_buffer_bind_group = &egui_transforms.bind_group.as_ref().unwrap().1;
-
         let mut requires_reset = true;
 
-        let mut vertex_offset: u32 = 0;
         for draw_command in &self.draw_commands {
             if requires_reset {
                 render_pass.set_viewport(
-                    0.,
-                    0.,
-                    physical_width as f32,
-                    physical_height as f32,
+                    draw_viewport.min.x as f32,
+                    draw_viewport.min.y as f32,
+                    draw_viewport.width() as f32,
+                    draw_viewport.height() as f32,
                     0.,
                     1.,
                 );
@@ -498,91 +1676,548 @@ impl Node for EguiNode {
                 requires_reset = false;
             }
 
-            let clip_urect = bevy_math::URect {
-                min: bevy_math::UVec2 {
-                    x: (draw_command.clip_rect.min.x * self.pixels_per_point).round() as u32,
-                    y: (draw_command.clip_rect.min.y * self.pixels_per_point).round() as u32,
-                },
-                max: bevy_math::UVec2 {
-                    x: (draw_command.clip_rect.max.x * self.pixels_per_point).round() as u32,
-                    y: (draw_command.clip_rect.max.y * self.pixels_per_point).round() as u32,
-                },
-            };
-
-            let scrissor_rect =
-                clip_urect.intersect(bevy_math::URect::new(0, 0, physical_width, physical_height));
-            if scrissor_rect.is_empty() {
+            let clip_urect = clip_rect_in_pixels(draw_command.clip_rect, self.pixels_per_point);
+            let scissor_rect = URect::from_corners(
+                clip_urect.min + draw_viewport.min,
+                clip_urect.max + draw_viewport.min,
+            )
+            .intersect(draw_viewport);
+            if scissor_rect.is_empty() {
                 continue;
             }
 
             render_pass.set_scissor_rect(
-                scrissor_rect.min.x,
-                scrissor_rect.min.y,
-                scrissor_rect.width(),
-                scrissor_rect.height(),
+                scissor_rect.min.x,
+                scissor_rect.min.y,
+                scissor_rect.width(),
+                scissor_rect.height(),
             );
 
             match &draw_command.primitive {
                 DrawPrimitive::Egui(command) => {
-                    let texture_bind_group = match bind_groups.get(&command.egui_texture) {
-                        Some(texture_resource) => texture_resource,
-                        None => {
-                            vertex_offset += command.vertices_count as u32;
-                            continue;
-                        }
+                    let (Some(texture_bind_group), Some((vertex_buffer, index_buffer))) =
+                        (bind_groups.get(&command.egui_texture), mesh_buffers)
+                    else {
+                        continue;
                     };
 
                     render_pass.set_bind_group(1, texture_bind_group, &[]);
 
-                    render_pass
-                        .set_vertex_buffer(0, self.vertex_buffer.as_ref().unwrap().slice(..));
-                    render_pass.set_index_buffer(
-                        self.index_buffer.as_ref().unwrap().slice(..),
-                        0,
-                        IndexFormat::Uint32,
-                    );
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(index_buffer.slice(..), 0, IndexFormat::Uint32);
 
                     render_pass.draw_indexed(
-                        vertex_offset..(vertex_offset + command.vertices_count as u32),
-                        0,
+                        command.first_index..(command.first_index + command.indices_count),
+                        command.base_vertex,
                         0..1,
                     );
-
-                    vertex_offset += command.vertices_count as u32;
                 }
                 DrawPrimitive::PaintCallback(command) => {
                     let info = egui::PaintCallbackInfo {
                         viewport: command.rect,
                         clip_rect: draw_command.clip_rect,
                         pixels_per_point: self.pixels_per_point,
-                        screen_size_px: [physical_width, physical_height],
+                        screen_size_px: draw_viewport.size().to_array(),
                     };
 
-                    let viewport = info.viewport_in_pixels();
-                    if viewport.width_px > 0 && viewport.height_px > 0 {
-                        requires_reset = true;
-                        render_pass.set_viewport(
-                            viewport.left_px as f32,
-                            viewport.top_px as f32,
-                            viewport.width_px as f32,
-                            viewport.height_px as f32,
-                            0.,
-                            1.,
-                        );
+                    let callback_viewport = info.viewport_in_pixels();
+                    if callback_viewport.width_px <= 0 || callback_viewport.height_px <= 0 {
+                        continue;
+                    }
 
-                        command.callback.cb().render(
+                    let callback = command.callback.cb();
+                    let callback_target = self.paint_callback_target(key, target_size, callback);
+                    requires_reset = true;
+
+                    if callback.depth_attachment() {
+                        // The depth texture may lag behind the render target for a frame
+                        // when it gets resized.
+                        let Some(depth_texture) =
+                            self.depth_texture.as_ref().filter(|depth_texture| {
+                                texture_size(depth_texture) == target_size
+                                    && depth_texture.texture.sample_count() == key.sample_count
+                            })
+                        else {
+                            continue;
+                        };
+
+                        // Callbacks that need depth get their own pass with a cleared depth
+                        // attachment, so that the Egui pipeline doesn't have to support it.
+                        drop(render_pass);
+                        let mut callback_pass = begin_render_pass(
+                            render_context.command_encoder(),
+                            device,
+                            "egui paint callback render pass",
+                            color_view,
+                            resolve_target,
+                            LoadOp::Load,
+                            Some(&depth_texture.default_view),
+                        );
+                        callback_pass.set_scissor_rect(
+                            scissor_rect.min.x,
+                            scissor_rect.min.y,
+                            scissor_rect.width(),
+                            scissor_rect.height(),
+                        );
+                        set_paint_callback_viewport(&mut callback_pass, &info, draw_viewport.min);
+                        callback.render(
                             info,
-                            &mut render_pass,
-                            self.window_entity,
-                            key,
+                            &mut callback_pass,
+                            self.target,
+                            &callback_target,
                             world,
                         );
+                        drop(callback_pass);
+
+                        render_pass = begin_render_pass(
+                            render_context.command_encoder(),
+                            device,
+                            S::PASS_LABEL,
+                            color_view,
+                            resolve_target,
+                            LoadOp::Load,
+                            None,
+                        );
+                        continue;
                     }
+
+                    set_paint_callback_viewport(&mut render_pass, &info, draw_viewport.min);
+                    callback.render(info, &mut render_pass, self.target, &callback_target, world);
+                }
+                DrawPrimitive::Backdrop(command) => {
+                    let (
+                        Some(((_, backdrop_bind_group), _)),
+                        Some(backdrop_pipeline),
+                        Some(backdrop_buffer),
+                    ) = (backdrop, backdrop_pipeline, self.backdrop_buffer.buffer())
+                    else {
+                        continue;
+                    };
+                    render_pass.set_render_pipeline(backdrop_pipeline);
+                    render_pass.set_bind_group(1, backdrop_bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, backdrop_buffer.slice(..));
+                    render_pass.draw(0..4, command.instance..command.instance + 1);
+                    requires_reset = true;
                 }
             }
         }
+        drop(render_pass);
 
-        Ok(())
+        if !key.uses_intermediate_texture() {
+            return true;
+        }
+        let Some((_, intermediate_bind_group)) = intermediate_texture else {
+            return false;
+        };
+        let Some(composite_pipeline) = self
+            .composite_pipeline
+            .and_then(|pipeline_id| pipeline_cache.get_render_pipeline(pipeline_id))
+        else {
+            return false;
+        };
+        let mut composite_pass = begin_render_pass(
+            render_context.command_encoder(),
+            device,
+            "egui composite render pass",
+            target_view.texture_view,
+            None,
+            target_load,
+            None,
+        );
+        composite_pass.set_viewport(
+            viewport.min.x as f32,
+            viewport.min.y as f32,
+            viewport.width() as f32,
+            viewport.height() as f32,
+            0.,
+            1.,
+        );
+        composite_pass.set_render_pipeline(composite_pipeline);
+        composite_pass.set_bind_group(0, transform_buffer_bind_group, &[transform_buffer_offset]);
+        composite_pass.set_bind_group(1, intermediate_bind_group, &[]);
+        composite_pass.draw(0..3, 0..1);
+        true
+    }
+
+    /// Draws the Egui meshes of the context into a render pass shared with other contexts,
+    /// paint callbacks and backdrop effects are skipped.
+    pub(crate) fn draw_in_pass<'a>(
+        &'a self,
+        render_pass: &mut TrackedRenderPass<'a>,
+        world: &'a World,
+    ) {
+        let Some(viewport) = self
+            .source
+            .view(self.target, world)
+            .map(|target_view| target_view.viewport)
+        else {
+            return;
+        };
+        let (Some(vertex_buffer), Some(index_buffer)) =
+            (self.vertex_buffer.buffer(), self.index_buffer.buffer())
+        else {
+            return;
+        };
+        let (
+            Some(EguiPipelines(egui_pipelines)),
+            Some(pipeline_cache),
+            Some(bind_groups),
+            Some(egui_transforms),
+        ) = (
+            self.render_resource::<EguiPipelines>(world),
+            self.render_resource::<PipelineCache>(world),
+            self.render_resource::<EguiTextureBindGroups>(world),
+            self.render_resource::<EguiTransforms>(world),
+        )
+        else {
+            return;
+        };
+        let (Some(&transform_buffer_offset), Some((_, transform_buffer_bind_group))) = (
+            egui_transforms.offsets.get(&self.target),
+            egui_transforms.bind_group.as_ref(),
+        ) else {
+            return;
+        };
+        let Some(pipeline) = egui_pipelines
+            .get(&self.target)
+            .and_then(|pipeline_id| pipeline_cache.get_render_pipeline(*pipeline_id))
+        else {
+            return;
+        };
+
+        render_pass.set_viewport(
+            viewport.min.x as f32,
+            viewport.min.y as f32,
+            viewport.width() as f32,
+            viewport.height() as f32,
+            0.,
+            1.,
+        );
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, transform_buffer_bind_group, &[transform_buffer_offset]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), 0, IndexFormat::Uint32);
+
+        for draw_command in &self.draw_commands {
+            let DrawPrimitive::Egui(command) = &draw_command.primitive else {
+                continue;
+            };
+            let Some(texture_bind_group) = bind_groups.get(&command.egui_texture) else {
+                continue;
+            };
+            let clip_urect = clip_rect_in_pixels(draw_command.clip_rect, self.pixels_per_point);
+            let scissor_rect =
+                URect::from_corners(clip_urect.min + viewport.min, clip_urect.max + viewport.min)
+                    .intersect(viewport);
+            if scissor_rect.is_empty() {
+                continue;
+            }
+            render_pass.set_scissor_rect(
+                scissor_rect.min.x,
+                scissor_rect.min.y,
+                scissor_rect.width(),
+                scissor_rect.height(),
+            );
+            render_pass.set_bind_group(1, texture_bind_group, &[]);
+            render_pass.draw_indexed(
+                command.first_index..(command.first_index + command.indices_count),
+                command.base_vertex,
+                0..1,
+            );
+        }
+    }
+
+    /// Keeps an offscreen texture matching the render target while a capture is pending.
+    fn prepare_capture_texture(&mut self, key: EguiPipelineKey, world: &World) {
+        if !*self.capture_pending.get_mut() {
+            self.capture_texture = None;
+            return;
+        }
+        let (Some(target_view), Some(render_device)) = (
+            self.source.view(self.target, world),
+            world.get_resource::<RenderDevice>(),
+        ) else {
+            return;
+        };
+        let size = target_view.size;
+        if self.capture_texture.as_ref().is_some_and(|(texture, _)| {
+            UVec2::new(texture.width(), texture.height()) == size
+                && texture.format() == key.texture_format
+        }) {
+            return;
+        }
+        let texture = render_device.create_texture(&TextureDescriptor {
+            label: Some("egui capture texture"),
+            size: Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: key.texture_format,
+            // Backdrop effects copy from the render target.
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let texture_view = texture.create_view(&TextureViewDescriptor::default());
+        self.capture_texture = Some((texture, texture_view));
+    }
+
+    /// Keeps the views of the mip levels in sync with the render target texture.
+    fn prepare_mip_chain(&mut self, world: &mut World) {
+        let texture = self
+            .source
+            .view(self.target, world)
+            .and_then(|view| view.texture.cloned())
+            .filter(|texture| texture.mip_level_count() > 1);
+        let Some(texture) = texture else {
+            self.mip_chain = None;
+            return;
+        };
+        if self
+            .mip_chain
+            .as_ref()
+            .is_some_and(|mip_chain| mip_chain.texture.id() == texture.id())
+        {
+            return;
+        }
+        if world.get_resource::<EguiMipmapPipeline>().is_none() {
+            return;
+        }
+
+        let pipeline = world.resource_scope(
+            |world, mut pipelines: Mut<SpecializedRenderPipelines<EguiMipmapPipeline>>| {
+                pipelines.specialize(
+                    world.resource::<PipelineCache>(),
+                    world.resource::<EguiMipmapPipeline>(),
+                    texture.format(),
+                )
+            },
+        );
+        let (Some(render_device), Some(mipmap_pipeline)) = (
+            self.render_resource::<RenderDevice>(world),
+            self.render_resource::<EguiMipmapPipeline>(world),
+        ) else {
+            return;
+        };
+        let level_views: Vec<_> = (0..texture.mip_level_count())
+            .map(|level| {
+                texture.create_view(&TextureViewDescriptor {
+                    label: Some("egui mip level view"),
+                    base_mip_level: level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        let level_bind_groups = level_views[..level_views.len() - 1]
+            .iter()
+            .map(|view| {
+                render_device.create_bind_group(
+                    Some("egui mip level bind group"),
+                    &mipmap_pipeline.texture_bind_group_layout,
+                    &[
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::TextureView(view),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::Sampler(&mipmap_pipeline.sampler),
+                        },
+                    ],
+                )
+            })
+            .collect();
+        self.mip_chain = Some(MipChain {
+            texture,
+            level_views,
+            level_bind_groups,
+            pipeline,
+        });
+    }
+
+    /// Returns the mip chain if it matches the render target texture.
+    fn mip_chain(&self, target_view: &EguiRenderTargetView) -> Option<&MipChain> {
+        self.mip_chain.as_ref().filter(|mip_chain| {
+            target_view
+                .texture
+                .is_some_and(|texture| texture.id() == mip_chain.texture.id())
+        })
+    }
+
+    /// Downsamples the first mip level of the render target into the others.
+    fn generate_mipmaps(&self, render_context: &mut RenderContext, world: &World) {
+        let Some(mip_chain) = self
+            .source
+            .view(self.target, world)
+            .and_then(|target_view| self.mip_chain(&target_view))
+        else {
+            return;
+        };
+        let (Some(pipeline_cache), Some(device)) = (
+            self.render_resource::<PipelineCache>(world),
+            self.render_resource::<RenderDevice>(world),
+        ) else {
+            return;
+        };
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(mip_chain.pipeline) else {
+            return;
+        };
+        for (bind_group, level_view) in mip_chain
+            .level_bind_groups
+            .iter()
+            .zip(&mip_chain.level_views[1..])
+        {
+            let mut mipmap_pass = begin_render_pass(
+                render_context.command_encoder(),
+                device,
+                "egui mipmap render pass",
+                level_view,
+                None,
+                LoadOp::Clear(wgpu_types::Color::TRANSPARENT),
+                None,
+            );
+            mipmap_pass.set_render_pipeline(pipeline);
+            mipmap_pass.set_bind_group(0, bind_group, &[]);
+            mipmap_pass.draw(0..3, 0..1);
+        }
+    }
+}
+
+/// Returns the physical size of the area Egui is drawn into, rounding fractional sizes of
+/// contexts rendered at a reduced resolution up.
+fn physical_size(render_target_size: &RenderTargetSize) -> UVec2 {
+    UVec2::new(
+        render_target_size.physical_width.ceil() as u32,
+        render_target_size.physical_height.ceil() as u32,
+    )
+}
+
+fn create_texture_bind_group(
+    render_device: &RenderDevice,
+    egui_pipeline: &EguiPipeline,
+    label: &'static str,
+    texture: &CachedTexture,
+    filter: FilterMode,
+) -> BindGroup {
+    let sampler = render_device.create_sampler(&SamplerDescriptor {
+        label: Some(label),
+        mag_filter: filter,
+        min_filter: filter,
+        ..Default::default()
+    });
+    render_device.create_bind_group(
+        Some(label),
+        &egui_pipeline.texture_bind_group_layout,
+        &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(&texture.default_view),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(&sampler),
+            },
+        ],
+    )
+}
+
+fn create_attachment_texture(
+    render_device: &RenderDevice,
+    label: &'static str,
+    size: UVec2,
+    format: TextureFormat,
+    sample_count: u32,
+    usage: TextureUsages,
+) -> CachedTexture {
+    let texture = render_device.create_texture(&TextureDescriptor {
+        label: Some(label),
+        size: Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: TextureDimension::D2,
+        format,
+        usage,
+        view_formats: &[],
+    });
+    let default_view = texture.create_view(&TextureViewDescriptor::default());
+    CachedTexture {
+        texture,
+        default_view,
+    }
+}
+
+pub(crate) fn begin_render_pass<'a>(
+    command_encoder: &'a mut CommandEncoder,
+    render_device: &RenderDevice,
+    label: &'static str,
+    texture_view: &'a TextureView,
+    resolve_target: Option<&'a TextureView>,
+    load: LoadOp<wgpu_types::Color>,
+    depth_view: Option<&'a TextureView>,
+) -> TrackedRenderPass<'a> {
+    let render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some(label),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view: texture_view,
+            resolve_target: resolve_target.map(|view| &**view),
+            ops: Operations {
+                load,
+                store: StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: depth_view.map(|view| RenderPassDepthStencilAttachment {
+            view,
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(1.0),
+                store: StoreOp::Discard,
+            }),
+            stencil_ops: None,
+        }),
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    TrackedRenderPass::new(render_device, render_pass)
+}
+
+fn set_paint_callback_viewport(
+    render_pass: &mut TrackedRenderPass,
+    info: &egui::PaintCallbackInfo,
+    offset: UVec2,
+) {
+    let viewport = info.viewport_in_pixels();
+    render_pass.set_viewport(
+        (viewport.left_px + offset.x as i32) as f32,
+        (viewport.top_px + offset.y as i32) as f32,
+        viewport.width_px as f32,
+        viewport.height_px as f32,
+        0.,
+        1.,
+    );
+}
+
+fn texture_size(texture: &CachedTexture) -> UVec2 {
+    let size = texture.texture.size();
+    UVec2::new(size.width, size.height)
+}
+
+fn clip_rect_in_pixels(clip_rect: egui::Rect, pixels_per_point: f32) -> bevy_math::URect {
+    bevy_math::URect {
+        min: bevy_math::UVec2 {
+            x: (clip_rect.min.x * pixels_per_point).round() as u32,
+            y: (clip_rect.min.y * pixels_per_point).round() as u32,
+        },
+        max: bevy_math::UVec2 {
+            x: (clip_rect.max.x * pixels_per_point).round() as u32,
+            y: (clip_rect.max.y * pixels_per_point).round() as u32,
+        },
     }
 }
 
@@ -652,7 +2287,7 @@ pub(crate) fn texture_options_as_sampler_descriptor(
     }
 }
 
-/// Callback to execute custom 'wgpu' rendering inside [`EguiNode`] render graph node.
+/// Callback to execute custom 'wgpu' rendering inside [`EguiRenderNode`] render graph node.
 ///
 /// Rendering can be implemented using for example:
 /// * native wgpu rendering libraries,
@@ -665,13 +2300,22 @@ impl EguiBevyPaintCallback {
     where
         T: EguiBevyPaintCallbackImpl + 'static,
     {
-        let callback = Self(Box::new(callback));
+        let callback = Self::new(callback);
         egui::epaint::PaintCallback {
             rect,
             callback: std::sync::Arc::new(callback),
         }
     }
 
+    /// Wraps a callback trait instance, is useful for implementing adapters for
+    /// [`EguiPaintCallbackRegistry`](crate::paint_callbacks::EguiPaintCallbackRegistry).
+    pub fn new<T>(callback: T) -> Self
+    where
+        T: EguiBevyPaintCallbackImpl + 'static,
+    {
+        Self(Box::new(callback))
+    }
+
     pub(crate) fn cb(&self) -> &dyn EguiBevyPaintCallbackImpl {
         self.0.as_ref()
     }
@@ -679,12 +2323,24 @@ impl EguiBevyPaintCallback {
 
 /// Callback that executes custom rendering logic
 pub trait EguiBevyPaintCallbackImpl: Send + Sync {
+    /// Returns `true` if the callback needs a depth attachment.
+    ///
+    /// Such callbacks are rendered in a separate render pass with a cleared depth attachment of
+    /// the [`EguiPaintCallbackTarget::DEPTH_FORMAT`] format, their pipelines must be specialized
+    /// with the [`EguiPaintCallbackTarget::depth_format`].
+    fn depth_attachment(&self) -> bool {
+        false
+    }
+
     /// Paint callback will be rendered in near future, all data must be finalized for render step
+    ///
+    /// GPU state that has to outlive the render pass can be stored in
+    /// [`EguiPaintCallbackResources`](crate::paint_callbacks::EguiPaintCallbackResources).
     fn update(
         &self,
         info: egui::PaintCallbackInfo,
         window_entity: Entity,
-        pipeline_key: EguiPipelineKey,
+        target: &EguiPaintCallbackTarget,
         world: &mut World,
     );
 
@@ -698,10 +2354,10 @@ pub trait EguiBevyPaintCallbackImpl: Send + Sync {
         info: egui::PaintCallbackInfo,
         render_context: &mut RenderContext<'w>,
         window_entity: Entity,
-        pipeline_key: EguiPipelineKey,
+        target: &EguiPaintCallbackTarget,
         world: &'w World,
     ) {
-        let _ = (info, render_context, window_entity, pipeline_key, world);
+        let _ = (info, render_context, window_entity, target, world);
         // Do nothing by default
     }
 
@@ -714,7 +2370,81 @@ pub trait EguiBevyPaintCallbackImpl: Send + Sync {
         info: egui::PaintCallbackInfo,
         render_pass: &mut TrackedRenderPass<'pass>,
         window_entity: Entity,
-        pipeline_key: EguiPipelineKey,
+        target: &EguiPaintCallbackTarget,
         world: &'pass World,
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_sample_count() {
+        let all = |_| true;
+        assert_eq!(supported_sample_count(0, all), 1);
+        assert_eq!(supported_sample_count(1, all), 1);
+        assert_eq!(supported_sample_count(2, all), 2);
+        assert_eq!(supported_sample_count(3, all), 4);
+        assert_eq!(supported_sample_count(8, all), 8);
+        // Values above 8 aren't clamped to 8, which may not be supported either.
+        assert_eq!(supported_sample_count(16, all), 4);
+
+        let up_to_four = |count| count <= 4;
+        assert_eq!(supported_sample_count(2, up_to_four), 2);
+        assert_eq!(supported_sample_count(8, up_to_four), 4);
+
+        let single = |count| count == 1;
+        assert_eq!(supported_sample_count(4, single), 1);
+        assert_eq!(supported_sample_count(2, single), 1);
+    }
+
+    #[test]
+    fn test_egui_buffer_grows() {
+        let mut buffer = EguiBuffer::new("test", BufferUsages::VERTEX);
+        let now = Instant::now();
+        assert!(!buffer.update_capacity(0, now));
+        assert!(buffer.update_capacity(100, now));
+        assert_eq!(buffer.capacity, EguiBuffer::MIN_CAPACITY);
+        assert!(!buffer.update_capacity(EguiBuffer::MIN_CAPACITY, now));
+        assert!(buffer.update_capacity(5000, now));
+        assert_eq!(buffer.capacity, 8192);
+    }
+
+    #[test]
+    fn test_egui_buffer_shrinks_after_delay() {
+        let mut buffer = EguiBuffer::new("test", BufferUsages::VERTEX);
+        let now = Instant::now();
+        assert!(buffer.update_capacity(100_000, now));
+        assert_eq!(buffer.capacity, 131_072);
+
+        assert!(!buffer.update_capacity(1000, now));
+        assert!(!buffer.update_capacity(1000, now + EguiBuffer::SHRINK_DELAY / 2));
+        assert!(buffer.update_capacity(10_000, now + EguiBuffer::SHRINK_DELAY));
+        assert_eq!(buffer.capacity, 32_768);
+        assert_eq!(buffer.oversized_since, None);
+    }
+
+    #[test]
+    fn test_egui_buffer_shrink_resets() {
+        let mut buffer = EguiBuffer::new("test", BufferUsages::VERTEX);
+        let now = Instant::now();
+        assert!(buffer.update_capacity(100_000, now));
+
+        assert!(!buffer.update_capacity(1000, now));
+        // A large write in between means the buffer isn't oversized anymore.
+        assert!(!buffer.update_capacity(100_000, now + EguiBuffer::SHRINK_DELAY / 2));
+        assert_eq!(buffer.oversized_since, None);
+        assert!(!buffer.update_capacity(1000, now + EguiBuffer::SHRINK_DELAY));
+        assert_eq!(buffer.capacity, 131_072);
+    }
+
+    #[test]
+    fn test_egui_buffer_keeps_min_capacity() {
+        let mut buffer = EguiBuffer::new("test", BufferUsages::VERTEX);
+        let now = Instant::now();
+        assert!(buffer.update_capacity(100, now));
+        assert!(!buffer.update_capacity(0, now + EguiBuffer::SHRINK_DELAY * 2));
+        assert_eq!(buffer.capacity, EguiBuffer::MIN_CAPACITY);
+    }
+}
@@ -0,0 +1,46 @@
+#import bevy_pbr::mesh_functions::{get_world_from_local, mesh_position_local_to_clip}
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) egui_position: vec2<f32>,
+    @location(4) clip_rect: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) egui_position: vec2<f32>,
+    @location(3) clip_rect: vec4<f32>,
+};
+
+@group(2) @binding(0) var image_texture: texture_2d<f32>;
+@group(2) @binding(1) var image_sampler: sampler;
+
+@vertex
+fn vertex(in: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = mesh_position_local_to_clip(
+        get_world_from_local(in.instance_index),
+        vec4<f32>(in.position, 1.0),
+    );
+    out.uv = in.uv;
+    out.color = in.color;
+    out.egui_position = in.egui_position;
+    out.clip_rect = in.clip_rect;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Sampled before discarding, derivatives need uniform control flow.
+    let texture_color = textureSample(image_texture, image_sampler, in.uv);
+    if any(in.egui_position < in.clip_rect.xy) || any(in.egui_position > in.clip_rect.zw) {
+        discard;
+    }
+    // This assumes that texture images are not premultiplied.
+    return in.color * vec4<f32>(texture_color.rgb * texture_color.a, texture_color.a);
+}
@@ -0,0 +1,628 @@
+use crate::{
+    egui_node::{begin_render_pass, EguiPipeline},
+    render_systems::{EguiTextureBindGroups, EguiTextureId},
+};
+use bevy_asset::Handle;
+use bevy_ecs::{prelude::*, world::FromWorld};
+use bevy_math::UVec2;
+use bevy_render::{
+    render_graph::{Node, NodeRunError, RenderGraphContext, RenderLabel},
+    render_resource::{
+        BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, BindingResource,
+        BindingType, BufferBindingType, BufferInitDescriptor, BufferUsages, CachedRenderPipelineId,
+        ColorTargetState, ColorWrites, Extent3d, FilterMode, FragmentState, LoadOp,
+        MultisampleState, PipelineCache, PrimitiveState, RenderPipelineDescriptor, Sampler,
+        SamplerDescriptor, Shader, ShaderDefVal, ShaderStages, SpecializedRenderPipeline,
+        SpecializedRenderPipelines, Texture, TextureAspect, TextureDescriptor, TextureDimension,
+        TextureFormat, TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor,
+        TextureViewDimension, TextureViewId, VertexState,
+    },
+    renderer::{RenderContext, RenderDevice},
+};
+use bevy_utils::HashMap;
+use bytemuck::cast_slice;
+use std::sync::{Arc, Mutex};
+
+/// Shader converting GPU textures into colors Egui can sample, see [`EguiGpuTexturePipeline`].
+pub const EGUI_GPU_TEXTURE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(6217793470128563517);
+
+/// Egui user texture ids of GPU textures have the highest bit set, so that they don't collide
+/// with the ids of [`EguiUserTextures`](crate::EguiUserTextures).
+const GPU_TEXTURE_ID_BIT: u64 = 1 << 63;
+
+/// Format of the textures GPU textures are converted into.
+const CONVERTED_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// Registry of render world textures shown as Egui user textures, such as depth buffers, shadow
+/// maps, G-buffer attachments or outputs of compute shaders.
+///
+/// The resource exists in both the main and the render world and shares the registered textures
+/// between them: ids are usually reserved in the main world, where the UI uses them, and
+/// textures are set in the render world, once they are prepared. Systems setting the textures
+/// run in [`RenderSet::PrepareBindGroups`](bevy_render::RenderSet::PrepareBindGroups), before
+/// [`prepare_gpu_textures_system`].
+///
+/// Textures that can't be sampled by Egui as they are (depth, integer, non-filterable or
+/// multisampled formats) and textures with a visualization other than
+/// [`EguiTextureVisualization::Color`] are converted into a color texture each frame, after
+/// cameras are rendered. Contexts drawn before that show them as of the previous frame:
+/// contexts rendered to textures, contexts with [`EguiRenderOrder::BeforeCameras`], and contexts
+/// with [`EguiRenderOrder::InCamera`], which are drawn inside the camera's render graph.
+///
+/// [`EguiRenderOrder::BeforeCameras`]: crate::EguiRenderOrder::BeforeCameras
+/// [`EguiRenderOrder::InCamera`]: crate::EguiRenderOrder::InCamera
+///
+/// ```rust
+/// use bevy::{
+///     prelude::*,
+///     render::{
+///         extract_resource::ExtractResource, render_resource::TextureUsages,
+///         view::ViewDepthTexture,
+///     },
+/// };
+/// use bevy_egui::{
+///     egui,
+///     gpu_textures::{EguiGpuTexture, EguiGpuTextures, EguiTextureVisualization},
+///     EguiContexts,
+/// };
+///
+/// // Add `ExtractResourcePlugin::<DepthTextureId>` to use the id in the render world.
+/// #[derive(Resource, Clone, ExtractResource)]
+/// struct DepthTextureId(egui::TextureId);
+///
+/// fn setup_system(mut commands: Commands, gpu_textures: Res<EguiGpuTextures>) {
+///     commands.insert_resource(DepthTextureId(gpu_textures.reserve()));
+///     // The depth texture must be created with `TextureUsages::TEXTURE_BINDING`.
+///     commands.spawn(Camera3dBundle {
+///         camera_3d: Camera3d {
+///             depth_texture_usages: (TextureUsages::RENDER_ATTACHMENT
+///                 | TextureUsages::TEXTURE_BINDING)
+///                 .into(),
+///             ..default()
+///         },
+///         ..default()
+///     });
+/// }
+///
+/// // Runs in the render world, in `RenderSet::PrepareBindGroups`.
+/// fn set_depth_texture_system(
+///     gpu_textures: Res<EguiGpuTextures>,
+///     depth_texture_id: Res<DepthTextureId>,
+///     depth_textures: Query<&ViewDepthTexture>,
+/// ) {
+///     for depth_texture in &depth_textures {
+///         let texture =
+///             EguiGpuTexture::from_texture(&depth_texture.texture, depth_texture.view().clone())
+///                 .with_visualization(EguiTextureVisualization::LinearDepth {
+///                     near: 0.1,
+///                     far: 100.0,
+///                 });
+///         gpu_textures.set(depth_texture_id.0, texture);
+///     }
+/// }
+///
+/// fn ui_system(mut contexts: EguiContexts, depth_texture_id: Res<DepthTextureId>) {
+///     egui::Window::new("Depth").show(contexts.ctx_mut(), |ui| {
+///         ui.image((depth_texture_id.0, egui::vec2(320.0, 180.0)));
+///     });
+/// }
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct EguiGpuTextures(Arc<Mutex<EguiGpuTexturesInner>>);
+
+#[derive(Default)]
+struct EguiGpuTexturesInner {
+    textures: HashMap<u64, EguiGpuTexture>,
+    last_texture_id: u64,
+}
+
+impl EguiGpuTextures {
+    /// Reserves an Egui texture id, the texture is set later with [`EguiGpuTextures::set`].
+    ///
+    /// Egui skips the meshes of a reserved id until its texture is set.
+    pub fn reserve(&self) -> egui::TextureId {
+        let Ok(mut inner) = self.0.lock() else {
+            return egui::TextureId::User(GPU_TEXTURE_ID_BIT);
+        };
+        let id = GPU_TEXTURE_ID_BIT | inner.last_texture_id;
+        inner.last_texture_id += 1;
+        egui::TextureId::User(id)
+    }
+
+    /// Registers the texture, returns a new Egui texture id.
+    pub fn add(&self, texture: EguiGpuTexture) -> egui::TextureId {
+        let id = self.reserve();
+        self.set(id, texture);
+        id
+    }
+
+    /// Sets the texture of the id, replacing the previous one.
+    ///
+    /// Textures recreated on resize, such as depth buffers, can be set every frame: the texture
+    /// is only prepared again if its view, size, format or visualization changes.
+    pub fn set(&self, id: egui::TextureId, texture: EguiGpuTexture) {
+        let egui::TextureId::User(id) = id else {
+            bevy_log::error!("Egui managed texture ids can't be used for GPU textures: {id:?}");
+            return;
+        };
+        if let Ok(mut inner) = self.0.lock() {
+            inner.textures.insert(id, texture);
+        }
+    }
+
+    /// Removes the texture, the id stays reserved and can be set again.
+    pub fn remove(&self, id: egui::TextureId) -> Option<EguiGpuTexture> {
+        let egui::TextureId::User(id) = id else {
+            return None;
+        };
+        self.0.lock().ok()?.textures.remove(&id)
+    }
+}
+
+/// A texture view registered in [`EguiGpuTextures`].
+#[derive(Clone, Debug)]
+pub struct EguiGpuTexture {
+    /// View of the texture, its texture needs [`TextureUsages::TEXTURE_BINDING`].
+    ///
+    /// The view must be two-dimensional, with a single mip level. Views of depth-stencil
+    /// formats must select [`TextureAspect::DepthOnly`].
+    pub view: TextureView,
+    /// Size of the texture, in pixels.
+    pub size: UVec2,
+    /// Format of the view.
+    pub format: TextureFormat,
+    /// Sample count of multisampled textures, only the first sample is shown.
+    pub sample_count: u32,
+    /// Sampler Egui uses, a linear one by default.
+    pub sampler: Option<Sampler>,
+    /// How the texture is shown.
+    pub visualization: EguiTextureVisualization,
+}
+
+impl EguiGpuTexture {
+    /// Constructs a texture from a view of the given size and format.
+    pub fn new(view: TextureView, size: UVec2, format: TextureFormat) -> Self {
+        Self {
+            view,
+            size,
+            format,
+            sample_count: 1,
+            sampler: None,
+            visualization: EguiTextureVisualization::default(),
+        }
+    }
+
+    /// Constructs a texture from a view, reading the size, format and sample count from the
+    /// texture.
+    pub fn from_texture(texture: &Texture, view: TextureView) -> Self {
+        Self {
+            sample_count: texture.sample_count(),
+            ..Self::new(
+                view,
+                UVec2::new(texture.width(), texture.height()),
+                texture.format(),
+            )
+        }
+    }
+
+    /// Sets [`EguiGpuTexture::sampler`], create it with
+    /// [`RenderDevice::create_sampler`](bevy_render::renderer::RenderDevice::create_sampler).
+    pub fn with_sampler(mut self, sampler: Sampler) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+
+    /// Sets [`EguiGpuTexture::visualization`].
+    pub fn with_visualization(mut self, visualization: EguiTextureVisualization) -> Self {
+        self.visualization = visualization;
+        self
+    }
+
+    fn sample_type(&self) -> EguiGpuTextureSampleType {
+        let sample_type = self.format.sample_type(None, None).or_else(|| {
+            self.format
+                .sample_type(Some(TextureAspect::DepthOnly), None)
+        });
+        match sample_type {
+            Some(TextureSampleType::Depth) => EguiGpuTextureSampleType::Depth,
+            Some(TextureSampleType::Uint) => EguiGpuTextureSampleType::Uint,
+            Some(TextureSampleType::Sint) => EguiGpuTextureSampleType::Sint,
+            Some(TextureSampleType::Float { filterable: true }) if self.sample_count == 1 => {
+                EguiGpuTextureSampleType::FilterableFloat
+            }
+            _ => EguiGpuTextureSampleType::Float,
+        }
+    }
+}
+
+/// How an [`EguiGpuTexture`] is shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EguiTextureVisualization {
+    /// Shows the colors as they are: single and two channel formats appear red and red-green,
+    /// depth formats show raw depth as grayscale.
+    #[default]
+    Color,
+    /// Shows a single channel as grayscale, mapping values from `min` to `max` to black and
+    /// white. Integer formats need a range to be visible, e.g. `0.0` to `255.0`.
+    Grayscale {
+        /// Index of the channel, from `0` (red or depth) to `3` (alpha).
+        channel: u32,
+        /// Value shown as black.
+        min: f32,
+        /// Value shown as white.
+        max: f32,
+    },
+    /// Shows the depth of a perspective camera (with Bevy's reversed infinite projection) as
+    /// grayscale, from white at the `near` plane to black at the `far` distance.
+    LinearDepth {
+        /// Near plane of the camera's projection.
+        near: f32,
+        /// View distance shown as black.
+        far: f32,
+    },
+}
+
+impl EguiTextureVisualization {
+    /// Mode, channel, min and max values of the conversion shader.
+    fn params(&self) -> [u32; 4] {
+        match *self {
+            Self::Color => [0, 0, 0, 0],
+            Self::Grayscale { channel, min, max } => [1, channel, min.to_bits(), max.to_bits()],
+            Self::LinearDepth { near, far } => [2, 0, near.to_bits(), far.to_bits()],
+        }
+    }
+}
+
+/// How the conversion shader reads a GPU texture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum EguiGpuTextureSampleType {
+    /// Float formats Egui can sample as they are.
+    FilterableFloat,
+    Float,
+    Depth,
+    Uint,
+    Sint,
+}
+
+/// Key for specialized [`EguiGpuTexturePipeline`] instances.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EguiGpuTexturePipelineKey {
+    sample_type: EguiGpuTextureSampleType,
+    multisampled: bool,
+}
+
+impl EguiGpuTexturePipelineKey {
+    fn shader_defs(&self) -> Vec<ShaderDefVal> {
+        let mut shader_defs = Vec::new();
+        match self.sample_type {
+            EguiGpuTextureSampleType::FilterableFloat | EguiGpuTextureSampleType::Float => {}
+            EguiGpuTextureSampleType::Depth => shader_defs.push("DEPTH".into()),
+            EguiGpuTextureSampleType::Uint => shader_defs.push("UINT".into()),
+            EguiGpuTextureSampleType::Sint => shader_defs.push("SINT".into()),
+        }
+        if self.multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+        }
+        shader_defs
+    }
+}
+
+/// Converts GPU textures registered in [`EguiGpuTextures`] into color textures Egui can sample.
+#[derive(Resource)]
+pub struct EguiGpuTexturePipeline {
+    bind_group_layouts: HashMap<EguiGpuTexturePipelineKey, BindGroupLayout>,
+    /// Sampler of textures that don't set their own.
+    default_sampler: Sampler,
+}
+
+impl FromWorld for EguiGpuTexturePipeline {
+    fn from_world(render_world: &mut World) -> Self {
+        let render_device = render_world.resource::<RenderDevice>();
+        let mut bind_group_layouts = HashMap::new();
+        for sample_type in [
+            EguiGpuTextureSampleType::Float,
+            EguiGpuTextureSampleType::Depth,
+            EguiGpuTextureSampleType::Uint,
+            EguiGpuTextureSampleType::Sint,
+        ] {
+            for multisampled in [false, true] {
+                let layout = render_device.create_bind_group_layout(
+                    "egui gpu texture bind group layout",
+                    &[
+                        BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: ShaderStages::FRAGMENT,
+                            ty: BindingType::Buffer {
+                                ty: BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                        BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: ShaderStages::FRAGMENT,
+                            ty: BindingType::Texture {
+                                sample_type: match sample_type {
+                                    EguiGpuTextureSampleType::Depth => TextureSampleType::Depth,
+                                    EguiGpuTextureSampleType::Uint => TextureSampleType::Uint,
+                                    EguiGpuTextureSampleType::Sint => TextureSampleType::Sint,
+                                    _ => TextureSampleType::Float { filterable: false },
+                                },
+                                view_dimension: TextureViewDimension::D2,
+                                multisampled,
+                            },
+                            count: None,
+                        },
+                    ],
+                );
+                let key = EguiGpuTexturePipelineKey {
+                    sample_type,
+                    multisampled,
+                };
+                bind_group_layouts.insert(key, layout);
+            }
+        }
+        let default_sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("egui gpu texture sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+        Self {
+            bind_group_layouts,
+            default_sampler,
+        }
+    }
+}
+
+impl EguiGpuTexturePipeline {
+    fn bind_group_layout(&self, key: EguiGpuTexturePipelineKey) -> &BindGroupLayout {
+        // Filterable textures are converted the same way as the other float ones.
+        let sample_type = match key.sample_type {
+            EguiGpuTextureSampleType::FilterableFloat => EguiGpuTextureSampleType::Float,
+            sample_type => sample_type,
+        };
+        &self.bind_group_layouts[&EguiGpuTexturePipelineKey { sample_type, ..key }]
+    }
+}
+
+impl SpecializedRenderPipeline for EguiGpuTexturePipeline {
+    type Key = EguiGpuTexturePipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: Some("egui gpu texture pipeline".into()),
+            layout: vec![self.bind_group_layout(key).clone()],
+            vertex: VertexState {
+                shader: EGUI_GPU_TEXTURE_SHADER_HANDLE,
+                shader_defs: Vec::new(),
+                entry_point: "vs_main".into(),
+                buffers: Vec::new(),
+            },
+            fragment: Some(FragmentState {
+                shader: EGUI_GPU_TEXTURE_SHADER_HANDLE,
+                shader_defs: key.shader_defs(),
+                entry_point: "fs_main".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: CONVERTED_TEXTURE_FORMAT,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+        }
+    }
+}
+
+/// GPU resources of a registered texture.
+struct PreparedEguiGpuTexture {
+    /// What the resources were created for, they're recreated when it changes.
+    source: (
+        TextureViewId,
+        UVec2,
+        TextureFormat,
+        EguiTextureVisualization,
+    ),
+    /// View Egui samples, either the registered one or the converted texture.
+    view: TextureView,
+    conversion: Option<EguiGpuTextureConversion>,
+}
+
+/// Draws a registered texture into a color texture.
+struct EguiGpuTextureConversion {
+    pipeline: CachedRenderPipelineId,
+    bind_group: BindGroup,
+}
+
+/// GPU resources of the textures registered in [`EguiGpuTextures`].
+#[derive(Resource, Default)]
+pub struct PreparedEguiGpuTextures(HashMap<u64, PreparedEguiGpuTexture>);
+
+/// Prepares the textures registered in [`EguiGpuTextures`] and queues their bind groups.
+#[allow(clippy::too_many_arguments)]
+pub fn prepare_gpu_textures_system(
+    gpu_textures: Res<EguiGpuTextures>,
+    mut prepared_textures: ResMut<PreparedEguiGpuTextures>,
+    mut bind_groups: ResMut<EguiTextureBindGroups>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    gpu_texture_pipeline: Res<EguiGpuTexturePipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<EguiGpuTexturePipeline>>,
+    egui_pipeline: Res<EguiPipeline>,
+) {
+    let Ok(gpu_textures) = gpu_textures.0.lock() else {
+        return;
+    };
+    prepared_textures
+        .0
+        .retain(|id, _| gpu_textures.textures.contains_key(id));
+
+    for (&id, texture) in &gpu_textures.textures {
+        let source = (
+            texture.view.id(),
+            texture.size,
+            texture.format,
+            texture.visualization,
+        );
+        let is_prepared = prepared_textures
+            .0
+            .get(&id)
+            .is_some_and(|prepared| prepared.source == source);
+        if !is_prepared {
+            let prepared = prepare_gpu_texture(
+                texture,
+                source,
+                &render_device,
+                &pipeline_cache,
+                &gpu_texture_pipeline,
+                &mut pipelines,
+            );
+            prepared_textures.0.insert(id, prepared);
+        }
+        let prepared = &prepared_textures.0[&id];
+
+        let sampler = texture
+            .sampler
+            .as_ref()
+            .unwrap_or(&gpu_texture_pipeline.default_sampler);
+        let bind_group = render_device.create_bind_group(
+            None,
+            &egui_pipeline.texture_bind_group_layout,
+            &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&prepared.view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(sampler),
+                },
+            ],
+        );
+        bind_groups.insert(EguiTextureId::User(id), bind_group);
+    }
+}
+
+fn prepare_gpu_texture(
+    texture: &EguiGpuTexture,
+    source: (
+        TextureViewId,
+        UVec2,
+        TextureFormat,
+        EguiTextureVisualization,
+    ),
+    render_device: &RenderDevice,
+    pipeline_cache: &PipelineCache,
+    gpu_texture_pipeline: &EguiGpuTexturePipeline,
+    pipelines: &mut SpecializedRenderPipelines<EguiGpuTexturePipeline>,
+) -> PreparedEguiGpuTexture {
+    let key = EguiGpuTexturePipelineKey {
+        sample_type: texture.sample_type(),
+        multisampled: texture.sample_count > 1,
+    };
+    if key.sample_type == EguiGpuTextureSampleType::FilterableFloat
+        && texture.visualization == EguiTextureVisualization::Color
+    {
+        return PreparedEguiGpuTexture {
+            source,
+            view: texture.view.clone(),
+            conversion: None,
+        };
+    }
+
+    let converted_texture = render_device.create_texture(&TextureDescriptor {
+        label: Some("egui gpu texture"),
+        size: Extent3d {
+            width: texture.size.x.max(1),
+            height: texture.size.y.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: CONVERTED_TEXTURE_FORMAT,
+        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let params = render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("egui gpu texture params buffer"),
+        contents: cast_slice(&texture.visualization.params()),
+        usage: BufferUsages::UNIFORM,
+    });
+    let bind_group = render_device.create_bind_group(
+        Some("egui gpu texture bind group"),
+        gpu_texture_pipeline.bind_group_layout(key),
+        &[
+            BindGroupEntry {
+                binding: 0,
+                resource: params.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::TextureView(&texture.view),
+            },
+        ],
+    );
+    PreparedEguiGpuTexture {
+        source,
+        view: converted_texture.create_view(&TextureViewDescriptor::default()),
+        conversion: Some(EguiGpuTextureConversion {
+            pipeline: pipelines.specialize(pipeline_cache, gpu_texture_pipeline, key),
+            bind_group,
+        }),
+    }
+}
+
+/// [`RenderLabel`] type for the pass converting GPU textures.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct EguiGpuTexturesPass;
+
+/// Converts the textures registered in [`EguiGpuTextures`] that Egui can't sample as they are.
+///
+/// Runs after cameras are rendered and before the Egui passes of windows and camera contexts.
+#[derive(Default)]
+pub struct EguiGpuTexturesNode;
+
+impl Node for EguiGpuTexturesNode {
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let (Some(prepared_textures), Some(pipeline_cache), Some(device)) = (
+            world.get_resource::<PreparedEguiGpuTextures>(),
+            world.get_resource::<PipelineCache>(),
+            world.get_resource::<RenderDevice>(),
+        ) else {
+            return Ok(());
+        };
+
+        for prepared in prepared_textures.0.values() {
+            let Some(conversion) = &prepared.conversion else {
+                continue;
+            };
+            let Some(pipeline) = pipeline_cache.get_render_pipeline(conversion.pipeline) else {
+                continue;
+            };
+            let mut render_pass = begin_render_pass(
+                render_context.command_encoder(),
+                device,
+                "egui gpu texture render pass",
+                &prepared.view,
+                None,
+                LoadOp::Clear(wgpu_types::Color::TRANSPARENT),
+                None,
+            );
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_bind_group(0, &conversion.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        Ok(())
+    }
+}
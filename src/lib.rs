@@ -57,15 +57,39 @@
 ))]
 compile_error!(include_str!("../static/error_web_sys_unstable_apis.txt"));
 
+/// Backdrop effects, such as blurring the scene behind Egui windows.
+#[cfg(feature = "render")]
+pub mod backdrop;
+/// Widget for rendering Bevy cameras inside Egui UI.
+#[cfg(feature = "render")]
+pub mod camera_viewport;
+/// Capturing the UI of Egui contexts to images.
+#[cfg(feature = "render")]
+pub mod capture;
+/// Egui render node for rendering many contexts into a texture atlas.
+#[cfg(feature = "render")]
+pub mod egui_atlas_node;
+/// Egui render node for rendering into camera viewports.
+#[cfg(feature = "render")]
+pub mod egui_camera_node;
 /// Egui render node.
 #[cfg(feature = "render")]
 pub mod egui_node;
 /// Egui render node for rendering to a texture.
 #[cfg(feature = "render")]
 pub mod egui_render_to_texture_node;
+/// Showing render world textures, such as depth buffers, as Egui user textures.
+#[cfg(feature = "render")]
+pub mod gpu_textures;
+/// Paint callback registry and adapters for paint callbacks of other types.
+#[cfg(feature = "render")]
+pub mod paint_callbacks;
 /// Plugin systems for the render app.
 #[cfg(feature = "render")]
 pub mod render_systems;
+/// Exporting the UI of Egui contexts as SVG documents.
+#[cfg(feature = "svg_export")]
+pub mod svg_export;
 /// Plugin systems.
 pub mod systems;
 /// Mobile web keyboard hacky input support
@@ -78,14 +102,17 @@ mod text_agent;
     web_sys_unstable_apis
 ))]
 pub mod web_clipboard;
+/// Drawing Egui contexts as meshes in the 3D world.
+#[cfg(feature = "world_space")]
+pub mod world_space;
 
 pub use egui;
 
 use crate::systems::*;
 #[cfg(feature = "render")]
 use crate::{
-    egui_node::{EguiPipeline, EGUI_SHADER_HANDLE},
-    render_systems::{EguiTransforms, ExtractedEguiManagedTextures},
+    egui_node::{EguiPipeline, EGUI_COMMON_SHADER_HANDLE, EGUI_SHADER_HANDLE},
+    render_systems::{EguiRenderErrors, EguiTransforms, ExtractedEguiManagedTextures},
 };
 #[cfg(all(
     feature = "manage_clipboard",
@@ -99,8 +126,11 @@ use bevy_asset::{load_internal_asset, AssetEvent, Assets, Handle};
 use bevy_render::{
     extract_component::{ExtractComponent, ExtractComponentPlugin},
     extract_resource::{ExtractResource, ExtractResourcePlugin},
-    render_resource::SpecializedRenderPipelines,
-    texture::{Image, ImageSampler},
+    render_graph::{InternedRenderLabel, RenderGraph, RenderLabel},
+    render_resource::{Extent3d, Shader, SpecializedRenderPipelines, TextureUsages},
+    texture::{
+        Image, ImageFilterMode, ImageSampler, ImageSamplerDescriptor, TextureFormatPixelInfo,
+    },
     ExtractSchedule, Render, RenderApp, RenderSet,
 };
 
@@ -121,6 +151,7 @@ use bevy_window::{PrimaryWindow, Window};
     not(any(target_arch = "wasm32", target_os = "android"))
 ))]
 use std::cell::{RefCell, RefMut};
+use std::time::Duration;
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
@@ -156,6 +187,55 @@ pub struct EguiSettings {
     /// }
     /// ```
     pub scale_factor: f32,
+    /// Enables reactive rendering for the context (`false` by default).
+    ///
+    /// When enabled, the context is re-tessellated and its GPU buffers are re-uploaded only when
+    /// Egui requests a repaint (respecting the [`egui::ViewportOutput::repaint_delay`]), the render
+    /// target gets resized, or textures change. Otherwise, the paint jobs of the previous frame
+    /// are reused, and render to texture contexts skip their render pass entirely, as their
+    /// images keep the previous frame.
+    ///
+    /// If your UI displays data that Egui doesn't know about (a game state, for example), make sure
+    /// to call [`egui::Context::request_repaint`] whenever this data changes.
+    pub reactive_rendering: bool,
+    /// Brightness of the UI white when rendering to HDR (float) targets, in nits
+    /// (`80.0` by default).
+    ///
+    /// HDR targets are assumed to be scRGB-like, where `1.0` corresponds to 80 nits, so the default
+    /// value renders the UI as bright as on SDR targets. Raise it to keep the UI readable next to
+    /// bright HDR content. Has no effect on SDR targets.
+    pub hdr_paper_white_nits: f32,
+    /// Blends Egui shapes in gamma space, like the reference `egui` renderers do (`false` by default).
+    ///
+    /// Semi-transparent shapes and gradients look the same as in `eframe` builds of the UI.
+    /// The context is drawn into an intermediate texture, which is composited onto the render
+    /// target afterwards; paint callbacks draw into that texture too.
+    pub gamma_blending: bool,
+    /// Applies ordered dithering to the output to avoid banding in gradients (`false` by default).
+    ///
+    /// Has no effect on HDR (float) targets.
+    pub dithering: bool,
+    /// Sample count for hardware antialiasing of Egui shapes and paint callbacks
+    /// (`1` by default, which disables MSAA).
+    ///
+    /// The context is drawn into an intermediate multisampled texture, which is resolved and
+    /// composited onto the render target afterwards. Sample counts are rounded up to the next
+    /// power of two (`2`, `4` or `8`). Counts the adapter doesn't support for the target format,
+    /// and counts above `8`, fall back to `4` (or `1`) with a warning.
+    pub msaa_samples: u32,
+    /// Fraction of the physical resolution Egui is rendered at (`1.0` by default).
+    ///
+    /// Lower values reduce the fill rate on high resolution displays: the context is rendered
+    /// into an intermediate texture, which is upscaled onto the render target with
+    /// [`EguiSettings::upscale_filter`]. The value is clamped to `0.1..=1.0`. Egui keeps the same
+    /// logical size, only its pixels per point get scaled.
+    ///
+    /// Contexts drawn into an [`EguiRenderToTextureAtlas`] region ignore it, they're always
+    /// rendered at the full resolution of the region.
+    pub render_scale: f32,
+    /// Filter used to upscale contexts rendered at a reduced resolution, see
+    /// [`EguiSettings::render_scale`].
+    pub upscale_filter: EguiUpscaleFilter,
     /// Is used as a default value for hyperlink [target](https://www.w3schools.com/tags/att_a_target.asp) hints.
     /// If not specified, `_self` will be used. Only matters in a web browser.
     #[cfg(feature = "open_url")]
@@ -178,12 +258,128 @@ impl Default for EguiSettings {
         Self {
             run_manually: false,
             scale_factor: 1.0,
+            reactive_rendering: false,
+            hdr_paper_white_nits: 80.0,
+            gamma_blending: false,
+            dithering: false,
+            msaa_samples: 1,
+            render_scale: 1.0,
+            upscale_filter: EguiUpscaleFilter::default(),
             #[cfg(feature = "open_url")]
             default_open_url_target: None,
         }
     }
 }
 
+impl EguiSettings {
+    /// Returns [`EguiSettings::render_scale`] clamped to the supported range.
+    pub(crate) fn clamped_render_scale(&self) -> f32 {
+        if self.render_scale.is_nan() {
+            return 1.0;
+        }
+        self.render_scale.clamp(0.1, 1.0)
+    }
+}
+
+/// Filter used to upscale contexts rendered at a reduced resolution,
+/// see [`EguiSettings::render_scale`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum EguiUpscaleFilter {
+    /// Bilinear filtering, looks smooth (default).
+    #[default]
+    Linear,
+    /// Nearest neighbour filtering, keeps the pixels sharp.
+    Nearest,
+}
+
+/// Errors that the plugin recovers from instead of panicking.
+///
+/// Every error is logged and sent as an event, read them with an [`EventReader<EguiError>`]
+/// to react to them.
+#[derive(Event, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EguiError {
+    /// Mutable access to the Egui context of the entity was requested more than once.
+    AliasedContextAccess(Entity),
+    /// The image of an [`EguiRenderToTextureHandle`] isn't loaded yet (or was removed),
+    /// the context is skipped until the image becomes available. It's sent once per target,
+    /// not every frame.
+    RenderToTextureImageNotLoaded(Entity),
+    /// An [`egui::PaintCallback`] isn't supported by any of the `EguiPaintCallbackRegistry`
+    /// adapters, the callback is skipped.
+    UnsupportedPaintCallback(Entity),
+    /// A render world resource required by the Egui render node of an entity is missing,
+    /// the frame isn't rendered for the entity.
+    MissingRenderResource {
+        /// Entity of the window or the render to texture target.
+        target: Entity,
+        /// Type name of the missing resource.
+        resource: &'static str,
+    },
+    /// The image requested with an [`EguiCapture`](capture::EguiCapture) couldn't be saved
+    /// to a PNG file.
+    CaptureNotSaved {
+        /// Entity of the captured context.
+        target: Entity,
+        /// Path of the file.
+        path: std::path::PathBuf,
+    },
+    /// The document requested with an `EguiSvgExport` couldn't be saved to a file.
+    SvgNotSaved {
+        /// Entity of the exported context.
+        target: Entity,
+        /// Path of the file.
+        path: std::path::PathBuf,
+    },
+}
+
+impl std::fmt::Display for EguiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AliasedContextAccess(entity) => write!(
+                f,
+                "Egui context of the entity {entity:?} was mutably accessed more than once"
+            ),
+            Self::RenderToTextureImageNotLoaded(entity) => write!(
+                f,
+                "Render to texture image of the entity {entity:?} isn't loaded"
+            ),
+            Self::UnsupportedPaintCallback(entity) => write!(
+                f,
+                "Unsupported Egui paint callback type (entity {entity:?}), \
+                 register an adapter for it in `EguiPaintCallbackRegistry`"
+            ),
+            Self::MissingRenderResource { target, resource } => write!(
+                f,
+                "Missing render resource `{resource}` for the Egui render node (entity {target:?})"
+            ),
+            Self::CaptureNotSaved { target, path } => write!(
+                f,
+                "Failed to save the Egui capture of the entity {target:?} to {path:?}"
+            ),
+            Self::SvgNotSaved { target, path } => write!(
+                f,
+                "Failed to save the SVG export of the entity {target:?} to {path:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EguiError {}
+
+impl EguiError {
+    /// Returns the entity the error refers to.
+    pub fn target(&self) -> Entity {
+        match self {
+            Self::AliasedContextAccess(entity)
+            | Self::RenderToTextureImageNotLoaded(entity)
+            | Self::UnsupportedPaintCallback(entity) => *entity,
+            Self::MissingRenderResource { target, .. }
+            | Self::CaptureNotSaved { target, .. }
+            | Self::SvgNotSaved { target, .. } => *target,
+        }
+    }
+}
+
 /// Is used for storing Egui context input.
 ///
 /// It gets reset during the [`EguiSet::ProcessInput`] system.
@@ -292,12 +488,15 @@ impl EguiClipboard {
 }
 
 /// Is used for storing Egui shapes and textures delta.
+///
+/// The component is extracted into the render world only when it changes, which allows render
+/// nodes to reuse the buffers of the previous frame (see [`EguiSettings::reactive_rendering`]).
 #[derive(Component, Clone, Default, Debug)]
-#[cfg_attr(feature = "render", derive(ExtractComponent))]
 pub struct EguiRenderOutput {
     /// Pairs of rectangles and paint commands.
     ///
-    /// The field gets populated during the [`EguiSet::ProcessOutput`] system (belonging to bevy's [`PostUpdate`]) and reset during `EguiNode::update`.
+    /// The field gets populated during the [`EguiSet::ProcessOutput`] system (belonging to bevy's [`PostUpdate`]).
+    /// With [`EguiSettings::reactive_rendering`] enabled, it's updated only when Egui requests a repaint.
     pub paint_jobs: Vec<egui::ClippedPrimitive>,
 
     /// The change in egui textures since last frame.
@@ -311,6 +510,19 @@ impl EguiRenderOutput {
     }
 }
 
+#[cfg(feature = "render")]
+impl ExtractComponent for EguiRenderOutput {
+    type QueryData = &'static Self;
+    type QueryFilter = Changed<Self>;
+    type Out = Self;
+
+    fn extract_component(
+        item: bevy_ecs::query::QueryItem<'_, Self::QueryData>,
+    ) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
 /// Is used for storing Egui output.
 #[derive(Component, Clone, Default)]
 pub struct EguiOutput {
@@ -326,6 +538,8 @@ pub struct EguiContext {
     mouse_position: egui::Pos2,
     pointer_touch_id: Option<u64>,
     has_sent_ime_enabled: bool,
+    /// Time (since startup) when the next repaint is due, used by reactive rendering.
+    next_repaint: Option<Duration>,
 }
 
 impl EguiContext {
@@ -362,8 +576,22 @@ impl EguiContext {
 #[cfg(not(feature = "render"))]
 type EguiContextsFilter = With<Window>;
 
-#[cfg(feature = "render")]
-type EguiContextsFilter = Or<(With<Window>, With<EguiRenderToTextureHandle>)>;
+#[cfg(all(feature = "render", not(feature = "world_space")))]
+type EguiContextsFilter = Or<(
+    With<Window>,
+    With<EguiRenderToTextureHandle>,
+    With<EguiRenderToTextureAtlasRegion>,
+    With<EguiCameraContext>,
+)>;
+
+#[cfg(feature = "world_space")]
+type EguiContextsFilter = Or<(
+    With<Window>,
+    With<EguiRenderToTextureHandle>,
+    With<EguiRenderToTextureAtlasRegion>,
+    With<EguiCameraContext>,
+    With<world_space::EguiWorldSpaceContext>,
+)>;
 
 #[derive(SystemParam)]
 /// A helper SystemParam that provides a way to get [`EguiContext`] with less boilerplate and
@@ -530,6 +758,20 @@ impl EguiContexts<'_, '_> {
         self.user_textures.add_image(image)
     }
 
+    /// Returns a handle that keeps the image registered as long as any of its clones is alive,
+    /// see [`EguiUserTextures::image_handle`].
+    #[cfg(feature = "render")]
+    pub fn image_handle(&mut self, image: Handle<Image>) -> EguiImageHandle {
+        self.user_textures.image_handle(image)
+    }
+
+    /// Registers the image for the current frame and returns its texture id, see
+    /// [`EguiUserTextures::frame_image`].
+    #[cfg(feature = "render")]
+    pub fn frame_image(&mut self, image: &Handle<Image>) -> egui::TextureId {
+        self.user_textures.frame_image(image)
+    }
+
     /// Removes the image handle and an Egui texture id associated with it.
     #[cfg(feature = "render")]
     #[track_caller]
@@ -551,12 +793,435 @@ impl EguiContexts<'_, '_> {
 #[derive(Component, Clone, Debug, ExtractComponent)]
 pub struct EguiRenderToTextureHandle(pub Handle<Image>);
 
+/// Requests the size of the image of an [`EguiRenderToTextureHandle`].
+///
+/// The image is reallocated whenever its size doesn't match the physical size (the logical size
+/// multiplied by the scale factor), and the context's [`RenderTargetSize`] gets the scale factor,
+/// so the UI keeps its logical size while the image resolution changes.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{EguiContext, EguiRenderToTextureHandle, EguiRenderToTextureSize};
+///
+/// fn ui_system(
+///     mut contexts: Query<
+///         (&mut EguiContext, &mut EguiRenderToTextureSize),
+///         With<EguiRenderToTextureHandle>,
+///     >,
+/// ) {
+///     for (mut ctx, mut render_to_texture_size) in contexts.iter_mut() {
+///         egui::CentralPanel::default().show(ctx.get_mut(), |ui| {
+///             if ui.button("Sharper").clicked() {
+///                 render_to_texture_size.scale_factor *= 2.0;
+///             }
+///         });
+///     }
+/// }
+/// ```
+#[cfg(feature = "render")]
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct EguiRenderToTextureSize {
+    /// Logical size of the UI, in points.
+    pub size: bevy_math::Vec2,
+    /// Physical pixels per logical point.
+    pub scale_factor: f32,
+}
+
+#[cfg(feature = "render")]
+impl EguiRenderToTextureSize {
+    /// Constructs the component with the logical size and the scale factor.
+    pub fn new(size: bevy_math::Vec2, scale_factor: f32) -> Self {
+        Self { size, scale_factor }
+    }
+
+    /// Returns the size of the image, in pixels.
+    pub fn physical_size(&self) -> bevy_math::UVec2 {
+        (self.size * self.scale_factor)
+            .round()
+            .as_uvec2()
+            .max(bevy_math::UVec2::ONE)
+    }
+}
+
+/// Image shared by many render to texture contexts, which are drawn into it in a single
+/// render pass.
+///
+/// Rendering lots of small UIs (world space labels or health bars, for example) each into its
+/// own image costs a render pass per context. Instead, spawn an entity with this component and
+/// bind the contexts to it with an [`EguiRenderToTextureAtlasRegion`]: the plugin packs the
+/// regions into the image, and materials sample their
+/// [`uv_rect`](EguiRenderToTextureAtlasRegion::uv_rect).
+///
+/// The image needs the `RENDER_ATTACHMENT` usage, and is cleared every frame. Contexts drawn
+/// into an atlas don't support gamma blending, MSAA, upscaling, backdrop effects and paint
+/// callbacks.
+///
+/// ```rust
+/// use bevy::{
+///     prelude::*,
+///     render::{
+///         render_asset::RenderAssetUsages,
+///         render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+///     },
+/// };
+/// use bevy_egui::{EguiRenderToTextureAtlas, EguiRenderToTextureAtlasRegion};
+///
+/// fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+///     let mut image = Image::new_fill(
+///         Extent3d {
+///             width: 1024,
+///             height: 1024,
+///             depth_or_array_layers: 1,
+///         },
+///         TextureDimension::D2,
+///         &[0; 4],
+///         TextureFormat::Rgba8UnormSrgb,
+///         RenderAssetUsages::default(),
+///     );
+///     image.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+///     let atlas = commands
+///         .spawn(EguiRenderToTextureAtlas::new(images.add(image)))
+///         .id();
+///     for _ in 0..10 {
+///         commands.spawn(EguiRenderToTextureAtlasRegion::new(
+///             atlas,
+///             Vec2::new(128.0, 32.0),
+///         ));
+///     }
+/// }
+/// ```
+#[cfg(feature = "render")]
+#[derive(Component, Clone, Debug, ExtractComponent)]
+pub struct EguiRenderToTextureAtlas {
+    /// Image the regions are packed into.
+    pub image: Handle<Image>,
+    /// Gap between the regions in pixels, which keeps texture filtering from bleeding across
+    /// them (`2` by default).
+    pub padding: u32,
+}
+
+#[cfg(feature = "render")]
+impl EguiRenderToTextureAtlas {
+    /// Constructs the atlas with the image.
+    pub fn new(image: Handle<Image>) -> Self {
+        Self { image, padding: 2 }
+    }
+}
+
+/// Binds an Egui context to a region of an [`EguiRenderToTextureAtlas`].
+///
+/// The region is allocated when the context is added or its size changes, regions that don't
+/// fit into the atlas aren't rendered. Allocated regions stay in place when other regions of
+/// the atlas are added, resized or removed, unless a new region doesn't fit into the free space:
+/// then all the regions are packed again, and their [`rect`](Self::rect) and
+/// [`uv_rect`](Self::uv_rect) may change. Read them again on
+/// `Changed<EguiRenderToTextureAtlasRegion>`, they also change when the atlas image is resized.
+#[cfg(feature = "render")]
+#[derive(Component, Clone, Debug, ExtractComponent)]
+pub struct EguiRenderToTextureAtlasRegion {
+    /// Atlas entity.
+    pub atlas: Entity,
+    /// Logical size of the UI, in points.
+    pub size: bevy_math::Vec2,
+    /// Physical pixels per logical point (`1.0` by default).
+    pub scale_factor: f32,
+    rect: Option<bevy_math::URect>,
+    uv_rect: Option<bevy_math::Rect>,
+}
+
+#[cfg(feature = "render")]
+impl EguiRenderToTextureAtlasRegion {
+    /// Constructs the region of the atlas with the logical size.
+    pub fn new(atlas: Entity, size: bevy_math::Vec2) -> Self {
+        Self {
+            atlas,
+            size,
+            scale_factor: 1.0,
+            rect: None,
+            uv_rect: None,
+        }
+    }
+
+    /// Sets [`EguiRenderToTextureAtlasRegion::scale_factor`].
+    pub fn with_scale_factor(mut self, scale_factor: f32) -> Self {
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    /// Returns the size of the region, in pixels.
+    pub fn physical_size(&self) -> bevy_math::UVec2 {
+        (self.size * self.scale_factor)
+            .round()
+            .as_uvec2()
+            .max(bevy_math::UVec2::ONE)
+    }
+
+    /// Returns the allocated rect in the atlas image, in pixels.
+    pub fn rect(&self) -> Option<bevy_math::URect> {
+        self.rect
+    }
+
+    /// Returns the allocated rect in UV coordinates of the atlas image.
+    ///
+    /// A [`StandardMaterial`](https://docs.rs/bevy/latest/bevy/pbr/struct.StandardMaterial.html)
+    /// displays the region with `uv_transform` set to
+    /// `Affine2::from_scale_angle_translation(uv_rect.size(), 0.0, uv_rect.min)`.
+    pub fn uv_rect(&self) -> Option<bevy_math::Rect> {
+        self.uv_rect
+    }
+}
+
+/// Sampling settings of the image of an [`EguiRenderToTextureHandle`], for images displayed
+/// on surfaces in the world.
+///
+/// Without mipmaps, a texture seen from a distance or at a grazing angle shimmers. With the
+/// component, the plugin allocates a mip chain for the image, which is regenerated from the
+/// first level after each Egui pass, and replaces the image sampler.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{EguiRenderToTextureHandle, EguiRenderToTextureSampling};
+///
+/// fn setup(mut commands: Commands, contexts: Query<Entity, With<EguiRenderToTextureHandle>>) {
+///     for context in contexts.iter() {
+///         commands
+///             .entity(context)
+///             .insert(EguiRenderToTextureSampling::default());
+///     }
+/// }
+/// ```
+#[cfg(feature = "render")]
+#[derive(Component, Clone, Copy, Debug)]
+pub struct EguiRenderToTextureSampling {
+    /// Generate a mip chain for the image (`true` by default).
+    pub mipmaps: bool,
+    /// Filter used for magnification, minification and between mip levels
+    /// (`Linear` by default).
+    pub filter: ImageFilterMode,
+    /// Maximum anisotropy of the sampler (`16` by default), `1` disables anisotropic filtering.
+    ///
+    /// Is ignored with the `Nearest` filter.
+    pub anisotropy_clamp: u16,
+}
+
+#[cfg(feature = "render")]
+impl Default for EguiRenderToTextureSampling {
+    fn default() -> Self {
+        Self {
+            mipmaps: true,
+            filter: ImageFilterMode::Linear,
+            anisotropy_clamp: 16,
+        }
+    }
+}
+
+#[cfg(feature = "render")]
+impl EguiRenderToTextureSampling {
+    fn sampler_descriptor(&self) -> ImageSamplerDescriptor {
+        // Anisotropic filtering requires all the filters to be linear.
+        let anisotropy_clamp = match self.filter {
+            ImageFilterMode::Linear => self.anisotropy_clamp.clamp(1, 16),
+            ImageFilterMode::Nearest => 1,
+        };
+        ImageSamplerDescriptor {
+            label: Some("egui render to texture sampler".into()),
+            mag_filter: self.filter,
+            min_filter: self.filter,
+            mipmap_filter: self.filter,
+            anisotropy_clamp,
+            ..ImageSamplerDescriptor::default()
+        }
+    }
+}
+
+/// Binds an Egui context to a camera, the UI is rendered into the camera's viewport.
+///
+/// Insert this component to a camera entity to get an independent Egui context for it
+/// (a HUD for each player viewport in a split-screen game, for example). Its screen rect
+/// follows the camera [`Viewport`](bevy_render::camera::Viewport).
+///
+/// Pointer input is filtered and offset to the viewport, keyboard input is sent to the camera
+/// contexts that want it (have a focused text field, for example). Touch and IME input are sent
+/// to window contexts only.
+#[cfg(feature = "render")]
+#[derive(Component, Clone, Debug, Default)]
+pub struct EguiCameraContext {
+    /// Window the camera renders to.
+    window: Option<Entity>,
+    /// Camera viewport in logical window pixels.
+    viewport: bevy_math::Rect,
+    /// Whether the pointer is inside the viewport.
+    hovered: bool,
+}
+
+/// Controls where the Egui pass of a window or camera context runs relative to cameras
+/// and Bevy UI.
+///
+/// Render to texture contexts ignore it: they are always rendered before cameras, so that
+/// the images are ready when cameras sample them.
+///
+/// There is no "before/after a specific camera" order: Bevy renders all cameras inside a single
+/// `CameraDriverLabel` node, so no pass can run between two of them. Use
+/// [`EguiRenderOrder::InCamera`] with the nodes of that camera's graph instead. Egui isn't
+/// queued as a phase item of the camera either, its node draws the whole context in a render
+/// pass of its own.
+///
+/// ```rust
+/// use bevy::{core_pipeline::core_3d::graph::Node3d, prelude::*};
+/// use bevy_egui::EguiRenderOrder;
+///
+/// fn setup(mut commands: Commands, windows: Query<Entity, With<Window>>) {
+///     let camera = commands.spawn(Camera3dBundle::default()).id();
+///     // Bevy UI is rendered after `EndMainPassPostProcessing`, so it covers Egui.
+///     let order = EguiRenderOrder::in_camera(
+///         camera,
+///         Node3d::Tonemapping,
+///         Node3d::EndMainPassPostProcessing,
+///     );
+///     for window in windows.iter() {
+///         commands.entity(window).insert(order);
+///     }
+/// }
+/// ```
+#[cfg(feature = "render")]
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, ExtractComponent)]
+pub enum EguiRenderOrder {
+    /// Egui is rendered after all cameras, on top of everything including Bevy UI (default).
+    #[default]
+    AfterCameras,
+    /// Egui is rendered before all cameras. Cameras rendering to the same target have to use
+    /// [`ClearColorConfig::None`](bevy_render::camera::ClearColorConfig::None) to keep it visible.
+    BeforeCameras,
+    /// Egui is rendered into the main texture of `camera` by a node of the camera's render graph,
+    /// which runs after the `after` node and before the `before` node.
+    ///
+    /// Use `Node3d::Tonemapping` and `Node3d::EndMainPassPostProcessing` (or their `Node2d`
+    /// counterparts) to let Bevy UI cover Egui, or `NodeUi::UiPass` and `Node3d::Upscaling`
+    /// to draw Egui over Bevy UI of this camera only. Both nodes have to be in the graph.
+    ///
+    /// GPU textures converted by [`EguiGpuTextures`](gpu_textures::EguiGpuTextures) show the
+    /// previous frame, as they're converted after cameras.
+    InCamera {
+        /// Camera entity.
+        camera: Entity,
+        /// The node Egui is rendered after.
+        after: InternedRenderLabel,
+        /// The node Egui is rendered before.
+        before: InternedRenderLabel,
+    },
+}
+
+#[cfg(feature = "render")]
+impl EguiRenderOrder {
+    /// Constructs [`EguiRenderOrder::InCamera`].
+    pub fn in_camera(camera: Entity, after: impl RenderLabel, before: impl RenderLabel) -> Self {
+        Self::InCamera {
+            camera,
+            after: after.intern(),
+            before: before.intern(),
+        }
+    }
+}
+
+/// Clears the render target of a context before Egui is drawn.
+///
+/// Without the component, contexts are drawn on top of what their render target contains:
+/// window and camera contexts over what cameras have rendered, render to texture contexts over
+/// the previous content of their image (and over the UI drawn the last time). Insert it to make
+/// windows without cameras show a defined background, or to redraw render to texture images
+/// from scratch.
+///
+/// The whole texture is cleared, even if the context is bound to a camera viewport, and
+/// contexts rendered [inside a camera graph](EguiRenderOrder::InCamera) clear the camera's
+/// main texture.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::EguiClearColor;
+///
+/// fn setup(mut commands: Commands, windows: Query<Entity, With<Window>>) {
+///     for window in windows.iter() {
+///         commands
+///             .entity(window)
+///             .insert(EguiClearColor::Color(egui::Color32::from_gray(27)));
+///     }
+/// }
+/// ```
+#[cfg(feature = "render")]
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, ExtractComponent)]
+pub enum EguiClearColor {
+    /// The render target isn't cleared (default).
+    #[default]
+    None,
+    /// The render target is cleared to transparent black.
+    Transparent,
+    /// The render target is cleared to the color, which is converted to the color space
+    /// of the target the same way Egui colors are.
+    Color(egui::Color32),
+}
+
+/// Replaces the fragment shader a context is drawn with.
+///
+/// The shader has to define a `fs_main` entry point taking the Egui `VertexOutput`.
+/// It's specialized with the same shader defs as the built-in one, so the simplest way to
+/// write it is to import the built-in fragment logic from the `bevy_egui::common` module and
+/// post-process its premultiplied output:
+///
+/// ```wgsl
+/// #import bevy_egui::common::{VertexOutput, egui_fragment, transform}
+///
+/// @fragment
+/// fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+///     // Fades the whole UI out.
+///     return egui_fragment(in) * transform.shader_params.x;
+/// }
+/// ```
+///
+/// Paint callbacks aren't affected by the shader.
+#[cfg(feature = "render")]
+#[derive(Component, Clone, Debug, ExtractComponent)]
+pub struct EguiShader {
+    /// Fragment shader.
+    pub shader: Handle<Shader>,
+    /// Parameters available to the shader as `transform.shader_params` (zeroes by default).
+    pub params: bevy_math::Vec4,
+}
+
+#[cfg(feature = "render")]
+impl EguiShader {
+    /// Constructs the component with zeroed parameters.
+    pub fn new(shader: Handle<Shader>) -> Self {
+        Self {
+            shader,
+            params: bevy_math::Vec4::ZERO,
+        }
+    }
+
+    /// Sets [`EguiShader::params`].
+    pub fn with_params(mut self, params: bevy_math::Vec4) -> Self {
+        self.params = params;
+        self
+    }
+}
+
 /// A resource for storing `bevy_egui` user textures.
 #[derive(Clone, bevy_ecs::system::Resource, Default, ExtractResource)]
 #[cfg(feature = "render")]
 pub struct EguiUserTextures {
     textures: bevy_utils::HashMap<Handle<Image>, u64>,
     last_texture_id: u64,
+    /// Textures that are freed once nothing keeps them registered.
+    owned_textures: bevy_utils::HashMap<u64, EguiUserTextureOwners>,
+}
+
+/// Keeps track of what keeps an automatically freed user texture registered.
+#[derive(Clone, Default)]
+#[cfg(feature = "render")]
+struct EguiUserTextureOwners {
+    /// State shared by the [`EguiImageHandle`] clones.
+    handle: std::sync::Weak<EguiImageHandleInner>,
+    /// Whether the texture was requested with [`EguiUserTextures::frame_image`] this frame.
+    used_this_frame: bool,
 }
 
 #[cfg(feature = "render")]
@@ -568,20 +1233,109 @@ impl EguiUserTextures {
     ///
     /// You'll want to pass a strong handle if a texture is used only in Egui and there are no
     /// handle copies stored anywhere else.
+    ///
+    /// The image stays registered until it's removed with [`EguiUserTextures::remove_image`] or
+    /// the asset is removed, even if it was registered by an [`EguiImageHandle`] or
+    /// [`EguiUserTextures::frame_image`] before.
     pub fn add_image(&mut self, image: Handle<Image>) -> egui::TextureId {
-        let id = *self.textures.entry(image.clone()).or_insert_with(|| {
-            let id = self.last_texture_id;
-            bevy_log::debug!("Add a new image (id: {}, handle: {:?})", id, image);
-            self.last_texture_id += 1;
-            id
-        });
+        let (id, _) = self.register(image);
+        self.owned_textures.remove(&id);
+        egui::TextureId::User(id)
+    }
+
+    /// Returns a handle that keeps the image registered as long as any of its clones is alive.
+    ///
+    /// Handles returned for the same image share the texture id. Once the last clone is dropped,
+    /// the texture is freed at the end of the frame, unless it's also registered with
+    /// [`EguiUserTextures::add_image`] or used with [`EguiUserTextures::frame_image`].
+    ///
+    /// Pass a strong handle to keep the asset loaded for as long as the texture is used.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_egui::{egui, EguiContexts, EguiImageHandle, EguiUserTextures};
+    ///
+    /// #[derive(Component)]
+    /// struct Icon(EguiImageHandle);
+    ///
+    /// fn spawn_icon_system(
+    ///     mut commands: Commands,
+    ///     asset_server: Res<AssetServer>,
+    ///     mut egui_user_textures: ResMut<EguiUserTextures>,
+    /// ) {
+    ///     let icon = egui_user_textures.image_handle(asset_server.load("icon.png"));
+    ///     commands.spawn(Icon(icon));
+    /// }
+    ///
+    /// fn ui_system(mut contexts: EguiContexts, icons: Query<&Icon>) {
+    ///     egui::Window::new("Icons").show(contexts.ctx_mut(), |ui| {
+    ///         for icon in &icons {
+    ///             ui.image(icon.0.sized_texture([32.0, 32.0]));
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn image_handle(&mut self, image: Handle<Image>) -> EguiImageHandle {
+        let (id, is_new) = self.register(image.clone());
+        let owners = match is_new {
+            true => Some(self.owned_textures.entry(id).or_default()),
+            false => self.owned_textures.get_mut(&id),
+        };
+        // Images registered with `add_image` aren't tracked, dropping the handle doesn't free them.
+        let Some(owners) = owners else {
+            return EguiImageHandle::new(image, id);
+        };
+        if let Some(inner) = owners.handle.upgrade() {
+            return EguiImageHandle(inner);
+        }
+        let handle = EguiImageHandle::new(image, id);
+        owners.handle = std::sync::Arc::downgrade(&handle.0);
+        handle
+    }
+
+    /// Registers the image for the current frame and returns its texture id.
+    ///
+    /// Call it every frame the image is shown: the texture is freed at the end of the first
+    /// frame it isn't requested in, unless it's also registered with
+    /// [`EguiUserTextures::add_image`] or kept by an [`EguiImageHandle`].
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_egui::{egui, EguiContexts};
+    ///
+    /// #[derive(Resource)]
+    /// struct Icons(Vec<Handle<Image>>);
+    ///
+    /// fn ui_system(mut contexts: EguiContexts, icons: Res<Icons>) {
+    ///     let icons: Vec<_> = icons.0.iter().map(|icon| contexts.frame_image(icon)).collect();
+    ///     egui::Window::new("Icons").show(contexts.ctx_mut(), |ui| {
+    ///         for icon in icons {
+    ///             ui.image((icon, egui::vec2(32.0, 32.0)));
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn frame_image(&mut self, image: &Handle<Image>) -> egui::TextureId {
+        let (id, is_new) = self.register(image.clone());
+        if is_new {
+            self.owned_textures
+                .insert(id, EguiUserTextureOwners::default());
+        }
+        if let Some(owners) = self.owned_textures.get_mut(&id) {
+            owners.used_this_frame = true;
+        }
         egui::TextureId::User(id)
     }
 
     /// Removes the image handle and an Egui texture id associated with it.
+    ///
+    /// [`EguiImageHandle`]s of the image keep the texture id, which is no longer valid.
     pub fn remove_image(&mut self, image: &Handle<Image>) -> Option<egui::TextureId> {
         let id = self.textures.remove(image);
         bevy_log::debug!("Remove image (id: {:?}, handle: {:?})", id, image);
+        if let Some(id) = id {
+            self.owned_textures.remove(&id);
+        }
         id.map(egui::TextureId::User)
     }
 
@@ -592,6 +1346,100 @@ impl EguiUserTextures {
             .get(image)
             .map(|&id| egui::TextureId::User(id))
     }
+
+    #[cfg(any(feature = "world_space", feature = "svg_export"))]
+    pub(crate) fn image(&self, id: u64) -> Option<&Handle<Image>> {
+        self.textures
+            .iter()
+            .find_map(|(image, &image_id)| (image_id == id).then_some(image))
+    }
+
+    /// Returns the id of the registered image, registering it if it's new.
+    fn register(&mut self, image: Handle<Image>) -> (u64, bool) {
+        if let Some(&id) = self.textures.get(&image) {
+            return (id, false);
+        }
+        let id = self.last_texture_id;
+        bevy_log::debug!("Add a new image (id: {}, handle: {:?})", id, image);
+        self.last_texture_id += 1;
+        self.textures.insert(image, id);
+        (id, true)
+    }
+
+    /// Frees the textures that are no longer kept by handles or used this frame, returns whether
+    /// any of them were freed.
+    fn free_unused_textures(&mut self) -> bool {
+        let mut unused = bevy_utils::HashSet::new();
+        self.owned_textures.retain(|&id, owners| {
+            let is_used = owners.used_this_frame || owners.handle.strong_count() > 0;
+            owners.used_this_frame = false;
+            if !is_used {
+                unused.insert(id);
+            }
+            is_used
+        });
+        if unused.is_empty() {
+            return false;
+        }
+        self.textures.retain(|image, id| {
+            if !unused.contains(id) {
+                return true;
+            }
+            bevy_log::debug!("Free unused image (id: {}, handle: {:?})", id, image);
+            false
+        });
+        true
+    }
+}
+
+/// A cloneable handle of an image registered as an Egui user texture, see
+/// [`EguiUserTextures::image_handle`].
+///
+/// The texture is freed once the last clone is dropped.
+#[derive(Clone, Debug)]
+#[cfg(feature = "render")]
+pub struct EguiImageHandle(std::sync::Arc<EguiImageHandleInner>);
+
+#[derive(Debug)]
+#[cfg(feature = "render")]
+struct EguiImageHandleInner {
+    image: Handle<Image>,
+    texture_id: egui::TextureId,
+}
+
+#[cfg(feature = "render")]
+impl EguiImageHandle {
+    fn new(image: Handle<Image>, id: u64) -> Self {
+        Self(std::sync::Arc::new(EguiImageHandleInner {
+            image,
+            texture_id: egui::TextureId::User(id),
+        }))
+    }
+
+    /// Egui texture id of the image.
+    #[must_use]
+    pub fn texture_id(&self) -> egui::TextureId {
+        self.0.texture_id
+    }
+
+    /// Handle of the image asset.
+    #[must_use]
+    pub fn image(&self) -> &Handle<Image> {
+        &self.0.image
+    }
+
+    /// Texture of the given size, can be passed to [`egui::Ui::image`].
+    #[must_use]
+    pub fn sized_texture(&self, size: impl Into<egui::Vec2>) -> egui::load::SizedTexture {
+        egui::load::SizedTexture::new(self.0.texture_id, size)
+    }
+}
+
+#[cfg(feature = "render")]
+impl From<&EguiImageHandle> for egui::TextureId {
+    fn from(handle: &EguiImageHandle) -> Self {
+        handle.texture_id()
+    }
 }
 
 /// Stores physical size and scale factor, is used as a helper to calculate logical size.
@@ -661,11 +1509,21 @@ pub enum EguiSet {
 impl Plugin for EguiPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<EguiSettings>();
+        app.add_event::<EguiError>();
 
         #[cfg(feature = "render")]
         {
             app.init_resource::<EguiManagedTextures>();
             app.init_resource::<EguiUserTextures>();
+            app.init_resource::<EguiRenderErrors>();
+            app.init_resource::<capture::EguiCaptureResults>();
+            app.init_resource::<gpu_textures::EguiGpuTextures>();
+            app.init_resource::<paint_callbacks::EguiPaintCallbackRegistry>();
+            app.add_event::<camera_viewport::EguiCameraViewportInput>();
+            app.add_event::<capture::EguiCaptured>();
+            app.add_plugins(ExtractResourcePlugin::<
+                paint_callbacks::EguiPaintCallbackRegistry,
+            >::default());
             app.add_plugins(ExtractResourcePlugin::<EguiUserTextures>::default());
             app.add_plugins(ExtractResourcePlugin::<ExtractedEguiManagedTextures>::default());
             app.add_plugins(ExtractComponentPlugin::<EguiContext>::default());
@@ -673,6 +1531,12 @@ impl Plugin for EguiPlugin {
             app.add_plugins(ExtractComponentPlugin::<RenderTargetSize>::default());
             app.add_plugins(ExtractComponentPlugin::<EguiRenderOutput>::default());
             app.add_plugins(ExtractComponentPlugin::<EguiRenderToTextureHandle>::default());
+            app.add_plugins(ExtractComponentPlugin::<EguiRenderToTextureAtlas>::default());
+            app.add_plugins(ExtractComponentPlugin::<EguiRenderToTextureAtlasRegion>::default());
+            app.add_plugins(ExtractComponentPlugin::<EguiRenderOrder>::default());
+            app.add_plugins(ExtractComponentPlugin::<EguiClearColor>::default());
+            app.add_plugins(ExtractComponentPlugin::<EguiShader>::default());
+            app.add_plugins(ExtractComponentPlugin::<capture::EguiCapture>::default());
         }
 
         #[cfg(target_arch = "wasm32")]
@@ -713,6 +1577,67 @@ impl Plugin for EguiPlugin {
                 .chain()
                 .in_set(EguiSet::InitContexts),
         );
+        #[cfg(feature = "render")]
+        {
+            app.add_systems(
+                PreStartup,
+                setup_camera_contexts_system
+                    .in_set(EguiStartupSet::InitContexts)
+                    .before(update_contexts_system),
+            );
+            app.add_systems(
+                PreUpdate,
+                setup_camera_contexts_system
+                    .in_set(EguiSet::InitContexts)
+                    .before(update_contexts_system),
+            );
+            app.add_systems(
+                PreUpdate,
+                resize_render_to_texture_images_system
+                    .in_set(EguiSet::InitContexts)
+                    .before(update_contexts_system),
+            );
+            app.add_systems(
+                PreStartup,
+                setup_atlas_regions_system
+                    .in_set(EguiStartupSet::InitContexts)
+                    .before(update_contexts_system),
+            );
+            app.add_systems(
+                PreUpdate,
+                (setup_atlas_regions_system, allocate_atlas_regions_system)
+                    .in_set(EguiSet::InitContexts)
+                    .before(update_contexts_system),
+            );
+        }
+        #[cfg(feature = "world_space")]
+        {
+            app.add_plugins(
+                bevy_pbr::MaterialPlugin::<world_space::EguiWorldSpaceMaterial> {
+                    prepass_enabled: false,
+                    shadows_enabled: false,
+                    ..Default::default()
+                },
+            );
+            app.add_systems(
+                PreStartup,
+                world_space::setup_world_space_contexts_system
+                    .in_set(EguiStartupSet::InitContexts)
+                    .before(update_contexts_system),
+            );
+            app.add_systems(
+                PreUpdate,
+                world_space::setup_world_space_contexts_system
+                    .in_set(EguiSet::InitContexts)
+                    .before(update_contexts_system),
+            );
+            app.add_systems(
+                PostUpdate,
+                world_space::update_world_space_meshes_system
+                    .after(update_egui_textures_system)
+                    .before(bevy_transform::TransformSystem::TransformPropagate),
+            );
+        }
         app.add_systems(
             PreUpdate,
             process_input_system
@@ -784,6 +1709,17 @@ impl Plugin for EguiPlugin {
             PostUpdate,
             process_output_system.in_set(EguiSet::ProcessOutput),
         );
+        #[cfg(feature = "svg_export")]
+        {
+            app.add_event::<svg_export::EguiSvgExported>();
+            // Shapes are consumed when the output is processed.
+            app.add_systems(
+                PostUpdate,
+                svg_export::export_svg_system
+                    .in_set(EguiSet::ProcessOutput)
+                    .before(process_output_system),
+            );
+        }
 
         #[cfg(feature = "render")]
         app.add_systems(
@@ -802,30 +1738,104 @@ impl Plugin for EguiPlugin {
             Render,
             render_systems::queue_pipelines_system.in_set(RenderSet::Queue),
         )
-        .add_systems(Last, free_egui_textures_system);
+        .add_systems(
+            PostUpdate,
+            camera_viewport::update_camera_viewports_system
+                .before(bevy_render::camera::CameraUpdateSystem),
+        )
+        // Runs after `resize_render_to_texture_images_system` (`PreUpdate`), so that resized
+        // images get their mip chains back in the same frame.
+        .add_systems(PostUpdate, update_render_to_texture_images_system)
+        .add_systems(Last, free_egui_textures_system)
+        .add_systems(First, forward_render_errors_system)
+        .add_systems(First, capture::forward_captures_system);
 
         #[cfg(feature = "render")]
         load_internal_asset!(
             app,
-            EGUI_SHADER_HANDLE,
-            "egui.wgsl",
-            bevy_render::render_resource::Shader::from_wgsl
+            EGUI_COMMON_SHADER_HANDLE,
+            "egui_common.wgsl",
+            Shader::from_wgsl
+        );
+        #[cfg(feature = "render")]
+        load_internal_asset!(app, EGUI_SHADER_HANDLE, "egui.wgsl", Shader::from_wgsl);
+        #[cfg(feature = "render")]
+        load_internal_asset!(
+            app,
+            egui_node::EGUI_MIPMAP_SHADER_HANDLE,
+            "egui_mipmap.wgsl",
+            Shader::from_wgsl
+        );
+        #[cfg(feature = "render")]
+        load_internal_asset!(
+            app,
+            gpu_textures::EGUI_GPU_TEXTURE_SHADER_HANDLE,
+            "egui_gpu_texture.wgsl",
+            Shader::from_wgsl
+        );
+        #[cfg(feature = "world_space")]
+        load_internal_asset!(
+            app,
+            world_space::EGUI_WORLD_SPACE_SHADER_HANDLE,
+            "egui_world_space.wgsl",
+            Shader::from_wgsl
         );
     }
 
     #[cfg(feature = "render")]
     fn finish(&self, app: &mut App) {
+        let render_errors = app.world().resource::<EguiRenderErrors>().clone();
+        let capture_results = app
+            .world()
+            .resource::<capture::EguiCaptureResults>()
+            .clone();
+        let gpu_textures = app
+            .world()
+            .resource::<gpu_textures::EguiGpuTextures>()
+            .clone();
         if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            let mut render_graph = render_app.world_mut().resource_mut::<RenderGraph>();
+            render_graph.add_node(
+                gpu_textures::EguiGpuTexturesPass,
+                gpu_textures::EguiGpuTexturesNode,
+            );
+            render_graph.add_node_edge(
+                bevy_render::graph::CameraDriverLabel,
+                gpu_textures::EguiGpuTexturesPass,
+            );
+
             render_app
+                .insert_resource(render_errors)
+                .insert_resource(capture_results)
+                .insert_resource(gpu_textures)
+                .init_resource::<gpu_textures::PreparedEguiGpuTextures>()
+                .init_resource::<capture::EguiCaptureReadbacks>()
+                .init_resource::<paint_callbacks::EguiPaintCallbackResources>()
                 .init_resource::<egui_node::EguiPipeline>()
                 .init_resource::<SpecializedRenderPipelines<EguiPipeline>>()
+                .init_resource::<egui_node::EguiCompositePipeline>()
+                .init_resource::<SpecializedRenderPipelines<egui_node::EguiCompositePipeline>>()
+                .init_resource::<egui_node::EguiBackdropPipeline>()
+                .init_resource::<SpecializedRenderPipelines<egui_node::EguiBackdropPipeline>>()
+                .init_resource::<egui_node::EguiMipmapPipeline>()
+                .init_resource::<SpecializedRenderPipelines<egui_node::EguiMipmapPipeline>>()
+                .init_resource::<gpu_textures::EguiGpuTexturePipeline>()
+                .init_resource::<SpecializedRenderPipelines<gpu_textures::EguiGpuTexturePipeline>>()
+                .init_resource::<render_systems::EguiTextureBindGroups>()
                 .init_resource::<EguiTransforms>()
                 .add_systems(
                     ExtractSchedule,
                     (
+                        // Removes the nodes first, so that contexts removed and re-added
+                        // within a frame keep theirs.
+                        render_systems::teardown_removed_contexts_render_system,
                         render_systems::setup_new_windows_render_system,
                         render_systems::setup_new_rtt_render_system,
-                    ),
+                        render_systems::setup_new_atlases_render_system,
+                        render_systems::setup_new_camera_contexts_render_system,
+                        render_systems::update_render_order_render_system,
+                    )
+                        .chain(),
                 )
                 .add_systems(
                     Render,
@@ -838,6 +1848,14 @@ impl Plugin for EguiPlugin {
                 .add_systems(
                     Render,
                     render_systems::queue_pipelines_system.in_set(RenderSet::Queue),
+                )
+                .add_systems(
+                    Render,
+                    gpu_textures::prepare_gpu_textures_system.in_set(RenderSet::PrepareBindGroups),
+                )
+                .add_systems(
+                    Render,
+                    capture::map_capture_readbacks_system.in_set(RenderSet::Cleanup),
                 );
         }
     }
@@ -869,6 +1887,24 @@ pub struct EguiContextQuery {
     /// [`EguiRenderToTextureHandle`] component, when rendering to a texture.
     #[cfg(feature = "render")]
     pub render_to_texture: Option<&'static mut EguiRenderToTextureHandle>,
+    /// [`EguiRenderToTextureSize`] component, if the size of the texture is requested.
+    #[cfg(feature = "render")]
+    pub render_to_texture_size: Option<&'static EguiRenderToTextureSize>,
+    /// [`EguiRenderToTextureAtlasRegion`] component, when rendering to a texture atlas.
+    #[cfg(feature = "render")]
+    pub atlas_region: Option<&'static EguiRenderToTextureAtlasRegion>,
+    /// [`bevy_render::camera::Camera`] component, when rendering to a camera viewport.
+    #[cfg(feature = "render")]
+    pub camera: Option<&'static bevy_render::camera::Camera>,
+    /// [`EguiCameraContext`] component, when rendering to a camera viewport.
+    #[cfg(feature = "render")]
+    pub camera_context: Option<&'static mut EguiCameraContext>,
+    /// [`EguiRenderOrder`] component, if the order differs from the default one.
+    #[cfg(feature = "render")]
+    pub render_order: Option<Ref<'static, EguiRenderOrder>>,
+    /// [`world_space::EguiWorldSpaceContext`] component, when drawing into the 3D world.
+    #[cfg(feature = "world_space")]
+    pub world_space: Option<&'static world_space::EguiWorldSpaceContext>,
 }
 
 impl EguiContextQueryItem<'_> {
@@ -947,19 +1983,272 @@ pub fn setup_render_to_texture_handles_system(
     }
 }
 
+/// Adds bevy_egui components to newly added camera contexts.
+#[cfg(feature = "render")]
+pub fn setup_camera_contexts_system(
+    mut commands: Commands,
+    new_camera_contexts: Query<Entity, (Added<EguiCameraContext>, Without<EguiContext>)>,
+) {
+    for camera in new_camera_contexts.iter() {
+        commands.entity(camera).insert((
+            EguiContext::default(),
+            EguiSettings::default(),
+            EguiRenderOutput::default(),
+            EguiInput::default(),
+            EguiFullOutput::default(),
+            EguiOutput::default(),
+            RenderTargetSize::default(),
+        ));
+    }
+}
+
+/// Packs [`EguiRenderToTextureAtlasRegion`]s into their atlases.
+#[cfg(feature = "render")]
+pub fn allocate_atlas_regions_system(
+    atlases: Query<(Entity, &EguiRenderToTextureAtlas)>,
+    mut regions: Query<(Entity, &mut EguiRenderToTextureAtlasRegion)>,
+    images: Res<Assets<Image>>,
+) {
+    for (atlas_entity, atlas) in atlases.iter() {
+        let Some(atlas_size) = images.get(&atlas.image).map(Image::size) else {
+            continue;
+        };
+        // Regions keep their rects, so that the UVs other regions were given stay valid.
+        let mut allocated = Vec::new();
+        let mut new_regions = Vec::new();
+        for (entity, region) in regions.iter() {
+            if region.atlas != atlas_entity {
+                continue;
+            }
+            let size = region.physical_size();
+            match region
+                .rect
+                .filter(|rect| rect.size() == size && rect.max.cmple(atlas_size).all())
+            {
+                Some(rect) => allocated.push((entity, rect)),
+                None => new_regions.push((entity, size)),
+            }
+        }
+        let allocated_rects = allocated.iter().map(|&(_, rect)| rect).collect();
+        let placed = place_atlas_regions(
+            atlas_size,
+            atlas.padding,
+            allocated_rects,
+            new_regions.clone(),
+        );
+        let rects: Vec<_> = if placed.iter().all(|(_, rect)| rect.is_some()) {
+            allocated
+                .into_iter()
+                .map(|(entity, rect)| (entity, Some(rect)))
+                .chain(placed)
+                .collect()
+        } else {
+            // The free space is too fragmented, all the regions are packed again.
+            let region_sizes = allocated
+                .into_iter()
+                .map(|(entity, rect)| (entity, rect.size()))
+                .chain(new_regions)
+                .collect();
+            pack_atlas_regions(atlas_size, atlas.padding, region_sizes)
+        };
+        for (entity, rect) in rects {
+            let Ok((_, mut region)) = regions.get_mut(entity) else {
+                continue;
+            };
+            // UVs depend on the atlas size too, they change if the atlas image gets resized.
+            let uv_rect = rect.map(|rect| {
+                bevy_math::Rect::from_corners(
+                    rect.min.as_vec2() / atlas_size.as_vec2(),
+                    rect.max.as_vec2() / atlas_size.as_vec2(),
+                )
+            });
+            if rect.is_none() && (region.rect.is_some() || region.is_added()) {
+                bevy_log::warn!("Atlas region of {entity:?} doesn't fit into {atlas_entity:?}");
+            }
+            if region.rect == rect && region.uv_rect == uv_rect {
+                continue;
+            }
+            region.rect = rect;
+            region.uv_rect = uv_rect;
+        }
+    }
+}
+
+/// Places regions of the given physical sizes into the space of an atlas that the `allocated`
+/// rects leave free, returns their rects (`None` if a region doesn't fit) in the order they were
+/// placed.
+#[cfg(feature = "render")]
+fn place_atlas_regions(
+    atlas_size: bevy_math::UVec2,
+    padding: u32,
+    mut allocated: Vec<bevy_math::URect>,
+    mut region_sizes: Vec<(Entity, bevy_math::UVec2)>,
+) -> Vec<(Entity, Option<bevy_math::URect>)> {
+    region_sizes.sort_by_key(|&(entity, size)| (std::cmp::Reverse(size.y), entity));
+    region_sizes
+        .into_iter()
+        .map(|(entity, size)| {
+            // A region fits either into a corner of the atlas, or next to (or below) another one.
+            let xs = std::iter::once(0).chain(allocated.iter().map(|rect| rect.max.x + padding));
+            let ys = std::iter::once(0).chain(allocated.iter().map(|rect| rect.max.y + padding));
+            let mut candidates: Vec<_> = ys
+                .flat_map(|y| xs.clone().map(move |x| bevy_math::UVec2::new(x, y)))
+                .collect();
+            candidates.sort_by_key(|min| (min.y, min.x));
+            let rect = candidates
+                .into_iter()
+                .map(|min| bevy_math::URect::from_corners(min, min + size))
+                .find(|rect| {
+                    rect.max.cmple(atlas_size).all()
+                        && allocated.iter().all(|other| {
+                            rect.min.x >= other.max.x + padding
+                                || other.min.x >= rect.max.x + padding
+                                || rect.min.y >= other.max.y + padding
+                                || other.min.y >= rect.max.y + padding
+                        })
+                });
+            allocated.extend(rect);
+            (entity, rect)
+        })
+        .collect()
+}
+
+/// Packs regions of the given physical sizes into shelves of an atlas, returns their rects
+/// (`None` if a region doesn't fit) in the order they were packed.
+#[cfg(feature = "render")]
+fn pack_atlas_regions(
+    atlas_size: bevy_math::UVec2,
+    padding: u32,
+    mut region_sizes: Vec<(Entity, bevy_math::UVec2)>,
+) -> Vec<(Entity, Option<bevy_math::URect>)> {
+    // Regions are packed into shelves, sorting them by height wastes less space.
+    region_sizes.sort_by_key(|&(entity, size)| (std::cmp::Reverse(size.y), entity));
+
+    let mut shelf_min = bevy_math::UVec2::ZERO;
+    let mut shelf_height = 0;
+    region_sizes
+        .into_iter()
+        .map(|(entity, size)| {
+            if shelf_min.x + size.x > atlas_size.x {
+                shelf_min = bevy_math::UVec2::new(0, shelf_min.y + shelf_height + padding);
+                shelf_height = 0;
+            }
+            let fits = shelf_min.x + size.x <= atlas_size.x && shelf_min.y + size.y <= atlas_size.y;
+            let rect = fits.then(|| bevy_math::URect::from_corners(shelf_min, shelf_min + size));
+            if fits {
+                shelf_min.x += size.x + padding;
+                shelf_height = shelf_height.max(size.y);
+            }
+            (entity, rect)
+        })
+        .collect()
+}
+
+/// Reallocates images of render to texture contexts to match their [`EguiRenderToTextureSize`].
+#[cfg(feature = "render")]
+pub fn resize_render_to_texture_images_system(
+    contexts: Query<(&EguiRenderToTextureHandle, &EguiRenderToTextureSize)>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (EguiRenderToTextureHandle(handle), render_to_texture_size) in contexts.iter() {
+        let size = render_to_texture_size.physical_size();
+        if images
+            .get(handle)
+            .map_or(true, |image| image.size() == size)
+        {
+            continue;
+        }
+        if let Some(image) = images.get_mut(handle) {
+            image.resize(Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            });
+        }
+    }
+}
+
+/// Allocates mip chains and sets samplers of images with an [`EguiRenderToTextureSampling`].
+#[cfg(feature = "render")]
+pub fn update_render_to_texture_images_system(
+    contexts: Query<(&EguiRenderToTextureHandle, &EguiRenderToTextureSampling)>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (EguiRenderToTextureHandle(handle), sampling) in contexts.iter() {
+        let Some(image) = images.get(handle) else {
+            continue;
+        };
+        let size = image.size();
+        let mip_level_count = match sampling.mipmaps {
+            true => 32 - size.x.max(size.y).max(1).leading_zeros(),
+            false => 1,
+        };
+        let pixel_size = image.texture_descriptor.format.pixel_size();
+        let data_size = (0..mip_level_count)
+            .map(|level| {
+                let level_size = (size >> level).max(bevy_math::UVec2::ONE);
+                (level_size.x * level_size.y) as usize * pixel_size
+            })
+            .sum();
+        let sampler_descriptor = sampling.sampler_descriptor();
+        let is_up_to_date = image.texture_descriptor.mip_level_count == mip_level_count
+            && image.data.len() == data_size
+            && matches!(&image.sampler, ImageSampler::Descriptor(descriptor)
+                if descriptor.as_wgpu() == sampler_descriptor.as_wgpu());
+        // Modifying the image re-uploads it, so it's done only when the settings change
+        // or the image gets replaced.
+        if is_up_to_date {
+            continue;
+        }
+
+        let Some(image) = images.get_mut(handle) else {
+            continue;
+        };
+        image.texture_descriptor.mip_level_count = mip_level_count;
+        // The levels are rendered to and sampled from when generating the mip chain.
+        image.texture_descriptor.usage |=
+            TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING;
+        // Image data has to contain all the levels, only the first one is ever rendered to.
+        image.data.resize(data_size, 0);
+        image.sampler = ImageSampler::Descriptor(sampler_descriptor);
+    }
+}
+
+/// Adds bevy_egui components to newly added atlas regions.
+#[cfg(feature = "render")]
+pub fn setup_atlas_regions_system(
+    mut commands: Commands,
+    new_regions: Query<Entity, (Added<EguiRenderToTextureAtlasRegion>, Without<EguiContext>)>,
+) {
+    for region in new_regions.iter() {
+        commands.entity(region).insert((
+            EguiContext::default(),
+            EguiSettings::default(),
+            EguiRenderOutput::default(),
+            EguiInput::default(),
+            EguiFullOutput::default(),
+            EguiOutput::default(),
+            RenderTargetSize::default(),
+        ));
+    }
+}
+
 /// Updates textures painted by Egui.
 #[cfg(feature = "render")]
 #[allow(clippy::type_complexity)]
 pub fn update_egui_textures_system(
-    mut egui_render_output: Query<
-        (Entity, &mut EguiRenderOutput),
-        Or<(With<Window>, With<EguiRenderToTextureHandle>)>,
-    >,
+    mut egui_render_output: Query<(Entity, &mut EguiRenderOutput), With<EguiContext>>,
     mut egui_managed_textures: ResMut<EguiManagedTextures>,
     mut image_assets: ResMut<Assets<Image>>,
 ) {
     for (entity, mut egui_render_output) in egui_render_output.iter_mut() {
-        let set_textures = std::mem::take(&mut egui_render_output.textures_delta.set);
+        // Consuming the delta shouldn't trigger extracting the paint jobs once again.
+        let set_textures = std::mem::take(
+            &mut egui_render_output
+                .bypass_change_detection()
+                .textures_delta
+                .set,
+        );
 
         for (texture_id, image_delta) in set_textures {
             let color_image = egui_node::as_color_image(image_delta.image);
@@ -998,12 +2287,18 @@ pub fn update_egui_textures_system(
             }
         }
     }
+}
 
-    fn update_image_rect(dest: &mut egui::ColorImage, [x, y]: [usize; 2], src: &egui::ColorImage) {
-        for sy in 0..src.height() {
-            for sx in 0..src.width() {
-                dest[(x + sx, y + sy)] = src[(sx, sy)];
-            }
+/// Copies the image of a partial texture update into the texture image.
+#[cfg(feature = "render")]
+pub(crate) fn update_image_rect(
+    dest: &mut egui::ColorImage,
+    [x, y]: [usize; 2],
+    src: &egui::ColorImage,
+) {
+    for sy in 0..src.height() {
+        for sx in 0..src.width() {
+            dest[(x + sx, y + sy)] = src[(sx, sy)];
         }
     }
 }
@@ -1012,16 +2307,19 @@ pub fn update_egui_textures_system(
 #[allow(clippy::type_complexity)]
 fn free_egui_textures_system(
     mut egui_user_textures: ResMut<EguiUserTextures>,
-    mut egui_render_output: Query<
-        (Entity, &mut EguiRenderOutput),
-        Or<(With<Window>, With<EguiRenderToTextureHandle>)>,
-    >,
+    mut egui_render_output: Query<(Entity, &mut EguiRenderOutput), With<EguiContext>>,
     mut egui_managed_textures: ResMut<EguiManagedTextures>,
     mut image_assets: ResMut<Assets<Image>>,
     mut image_events: EventReader<AssetEvent<Image>>,
+    mut removed_contexts: RemovedComponents<EguiContext>,
 ) {
     for (entity, mut egui_render_output) in egui_render_output.iter_mut() {
-        let free_textures = std::mem::take(&mut egui_render_output.textures_delta.free);
+        let free_textures = std::mem::take(
+            &mut egui_render_output
+                .bypass_change_detection()
+                .textures_delta
+                .free,
+        );
         for texture_id in free_textures {
             if let egui::TextureId::Managed(texture_id) = texture_id {
                 let managed_texture = egui_managed_textures.remove(&(entity, texture_id));
@@ -1032,11 +2330,52 @@ fn free_egui_textures_system(
         }
     }
 
+    // Free textures of despawned windows and render to texture targets.
+    for entity in removed_contexts.read() {
+        egui_managed_textures.retain(|&(texture_entity, _), managed_texture| {
+            if texture_entity != entity {
+                return true;
+            }
+            image_assets.remove(&managed_texture.handle);
+            false
+        });
+    }
+
     for image_event in image_events.read() {
         if let AssetEvent::Removed { id } = image_event {
             egui_user_textures.remove_image(&Handle::<Image>::Weak(*id));
         }
     }
+
+    // Only mark the textures as changed (to be extracted again) if any of them are freed.
+    if egui_user_textures
+        .bypass_change_detection()
+        .free_unused_textures()
+    {
+        egui_user_textures.set_changed();
+    }
+}
+
+#[cfg(feature = "render")]
+fn forward_render_errors_system(
+    render_errors: Res<EguiRenderErrors>,
+    mut logged_errors: Local<bevy_utils::HashSet<EguiError>>,
+    mut removed_contexts: RemovedComponents<EguiContext>,
+    mut egui_errors: EventWriter<EguiError>,
+) {
+    for entity in removed_contexts.read() {
+        logged_errors.retain(|error| error.target() != entity);
+    }
+    for error in render_errors.drain() {
+        // Render errors tend to repeat every frame, logging each of them once is enough.
+        if logged_errors.insert(error.clone()) {
+            match error {
+                EguiError::UnsupportedPaintCallback(_) => bevy_log::warn!("{error}"),
+                _ => bevy_log::error!("{error}"),
+            }
+        }
+        egui_errors.send(error);
+    }
 }
 
 /// Helper function for outputting a String from a JsValue
@@ -1111,6 +2450,304 @@ mod tests {
         version_sync::assert_markdown_deps_updated!("README.md");
     }
 
+    #[test]
+    fn test_egui_error_display() {
+        let entity = Entity::from_raw(7);
+        assert_eq!(
+            EguiError::AliasedContextAccess(entity).to_string(),
+            format!("Egui context of the entity {entity:?} was mutably accessed more than once")
+        );
+        assert_eq!(
+            EguiError::RenderToTextureImageNotLoaded(entity).to_string(),
+            format!("Render to texture image of the entity {entity:?} isn't loaded")
+        );
+        assert_eq!(
+            EguiError::UnsupportedPaintCallback(entity).to_string(),
+            format!(
+                "Unsupported Egui paint callback type (entity {entity:?}), \
+                 register an adapter for it in `EguiPaintCallbackRegistry`"
+            )
+        );
+        assert_eq!(
+            EguiError::MissingRenderResource {
+                target: entity,
+                resource: "EguiPipeline",
+            }
+            .to_string(),
+            format!(
+                "Missing render resource `EguiPipeline` for the Egui render node \
+                 (entity {entity:?})"
+            )
+        );
+        let path = std::path::PathBuf::from("out/egui.png");
+        assert_eq!(
+            EguiError::CaptureNotSaved {
+                target: entity,
+                path: path.clone(),
+            }
+            .to_string(),
+            format!("Failed to save the Egui capture of the entity {entity:?} to \"out/egui.png\"")
+        );
+        assert_eq!(
+            EguiError::SvgNotSaved {
+                target: entity,
+                path,
+            }
+            .to_string(),
+            format!("Failed to save the SVG export of the entity {entity:?} to \"out/egui.png\"")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_pack_atlas_regions_fits_into_shelves() {
+        let [a, b, c] = [
+            Entity::from_raw(0),
+            Entity::from_raw(1),
+            Entity::from_raw(2),
+        ];
+        let sizes = vec![
+            (a, bevy_math::UVec2::new(60, 20)),
+            (b, bevy_math::UVec2::new(40, 30)),
+            (c, bevy_math::UVec2::new(50, 10)),
+        ];
+        let rects = pack_atlas_regions(bevy_math::UVec2::new(100, 100), 0, sizes);
+        let rect = |min: [u32; 2], max: [u32; 2]| {
+            Some(bevy_math::URect::from_corners(min.into(), max.into()))
+        };
+        // The tallest region goes first, the next ones fill the shelf or start a new one.
+        assert_eq!(
+            rects,
+            vec![
+                (b, rect([0, 0], [40, 30])),
+                (a, rect([40, 0], [100, 20])),
+                (c, rect([0, 30], [50, 40])),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_pack_atlas_regions_applies_padding() {
+        let [a, b, c] = [
+            Entity::from_raw(0),
+            Entity::from_raw(1),
+            Entity::from_raw(2),
+        ];
+        let size = bevy_math::UVec2::new(40, 40);
+        let rects = pack_atlas_regions(
+            bevy_math::UVec2::new(100, 100),
+            4,
+            vec![(a, size), (b, size), (c, size)],
+        );
+        let mins: Vec<_> = rects
+            .iter()
+            .map(|(_, rect)| rect.map(|rect| rect.min.to_array()))
+            .collect();
+        assert_eq!(mins, vec![Some([0, 0]), Some([44, 0]), Some([0, 44])]);
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_pack_atlas_regions_overflow() {
+        let [a, b] = [Entity::from_raw(0), Entity::from_raw(1)];
+        let rects = pack_atlas_regions(
+            bevy_math::UVec2::new(100, 50),
+            0,
+            vec![
+                (a, bevy_math::UVec2::new(100, 40)),
+                (b, bevy_math::UVec2::new(100, 20)),
+            ],
+        );
+        assert!(rects[0].1.is_some());
+        // The second shelf would end below the atlas.
+        assert_eq!(rects[1], (b, None));
+
+        let too_wide = pack_atlas_regions(
+            bevy_math::UVec2::new(100, 100),
+            0,
+            vec![(a, bevy_math::UVec2::new(101, 10))],
+        );
+        assert_eq!(too_wide, vec![(a, None)]);
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_pack_atlas_regions_is_deterministic() {
+        let entities: Vec<_> = (0..4).map(Entity::from_raw).collect();
+        let size = bevy_math::UVec2::new(30, 30);
+        let sizes: Vec<_> = entities.iter().map(|&entity| (entity, size)).collect();
+        let mut reversed = sizes.clone();
+        reversed.reverse();
+        // Regions of the same height are ordered by entity, whatever order they're queried in.
+        let rects = pack_atlas_regions(bevy_math::UVec2::new(100, 100), 0, sizes);
+        assert_eq!(
+            rects,
+            pack_atlas_regions(bevy_math::UVec2::new(100, 100), 0, reversed)
+        );
+        let order: Vec<_> = rects.iter().map(|&(entity, _)| entity).collect();
+        assert_eq!(order, entities);
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_place_atlas_regions_around_allocated() {
+        let [a, b] = [Entity::from_raw(0), Entity::from_raw(1)];
+        let allocated = vec![bevy_math::URect::new(0, 0, 40, 40)];
+        let rects = place_atlas_regions(
+            bevy_math::UVec2::new(100, 100),
+            2,
+            allocated,
+            vec![
+                (a, bevy_math::UVec2::new(50, 40)),
+                (b, bevy_math::UVec2::new(60, 30)),
+            ],
+        );
+        assert_eq!(
+            rects,
+            vec![
+                (a, Some(bevy_math::URect::new(42, 0, 92, 40))),
+                (b, Some(bevy_math::URect::new(0, 42, 60, 72))),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_place_atlas_regions_fills_freed_space() {
+        let a = Entity::from_raw(0);
+        // The region between the two allocated ones was freed.
+        let allocated = vec![
+            bevy_math::URect::new(0, 0, 30, 30),
+            bevy_math::URect::new(64, 0, 94, 30),
+        ];
+        let rects = place_atlas_regions(
+            bevy_math::UVec2::new(100, 100),
+            2,
+            allocated.clone(),
+            vec![(a, bevy_math::UVec2::new(30, 30))],
+        );
+        assert_eq!(rects, vec![(a, Some(bevy_math::URect::new(32, 0, 62, 30)))]);
+
+        let rects = place_atlas_regions(
+            bevy_math::UVec2::new(100, 30),
+            2,
+            allocated,
+            vec![(a, bevy_math::UVec2::new(40, 30))],
+        );
+        assert_eq!(rects, vec![(a, None)]);
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_allocate_atlas_regions_keeps_allocated_regions() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        let mut images = Assets::<Image>::default();
+        let image = images.add(Image::new_fill(
+            Extent3d {
+                width: 100,
+                height: 100,
+                depth_or_array_layers: 1,
+            },
+            bevy_render::render_resource::TextureDimension::D2,
+            &[0; 4],
+            bevy_render::render_resource::TextureFormat::Rgba8UnormSrgb,
+            bevy_render::render_asset::RenderAssetUsages::default(),
+        ));
+        world.insert_resource(images);
+        let atlas = world.spawn(EguiRenderToTextureAtlas::new(image)).id();
+        let region = |size: [f32; 2]| EguiRenderToTextureAtlasRegion::new(atlas, size.into());
+        let small = world.spawn(region([20.0, 20.0])).id();
+        world.run_system_once(allocate_atlas_regions_system);
+        let rect = |world: &World, entity| {
+            world
+                .get::<EguiRenderToTextureAtlasRegion>(entity)
+                .unwrap()
+                .rect()
+        };
+        let small_rect = rect(&world, small);
+        assert!(small_rect.is_some());
+
+        // A taller region would go first if everything was packed again.
+        let tall = world.spawn(region([20.0, 60.0])).id();
+        world.run_system_once(allocate_atlas_regions_system);
+        assert_eq!(rect(&world, small), small_rect);
+        assert!(rect(&world, tall).is_some());
+
+        // Resizing a region only moves that region.
+        let tall_rect = rect(&world, tall);
+        world
+            .get_mut::<EguiRenderToTextureAtlasRegion>(small)
+            .unwrap()
+            .size = bevy_math::Vec2::new(30.0, 20.0);
+        world.run_system_once(allocate_atlas_regions_system);
+        assert_eq!(rect(&world, tall), tall_rect);
+        assert_eq!(
+            rect(&world, small).map(|rect| rect.size()),
+            Some(bevy_math::UVec2::new(30, 20))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_image_handle_frees_texture_after_last_clone() {
+        let mut user_textures = EguiUserTextures::default();
+        let image = Handle::<Image>::weak_from_u128(1);
+        let handle = user_textures.image_handle(image.clone());
+        let clone = user_textures.image_handle(image.clone());
+        assert_eq!(handle.texture_id(), clone.texture_id());
+        assert_eq!(user_textures.image_id(&image), Some(handle.texture_id()));
+
+        drop(handle);
+        assert!(!user_textures.free_unused_textures());
+        assert!(user_textures.image_id(&image).is_some());
+
+        drop(clone);
+        assert!(user_textures.free_unused_textures());
+        assert_eq!(user_textures.image_id(&image), None);
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_frame_image_is_kept_for_one_frame() {
+        let mut user_textures = EguiUserTextures::default();
+        let image = Handle::<Image>::weak_from_u128(1);
+        let texture_id = user_textures.frame_image(&image);
+        assert_eq!(user_textures.frame_image(&image), texture_id);
+
+        // Used this frame.
+        assert!(!user_textures.free_unused_textures());
+        assert_eq!(user_textures.image_id(&image), Some(texture_id));
+        // Not requested in the next frame.
+        assert!(user_textures.free_unused_textures());
+        assert_eq!(user_textures.image_id(&image), None);
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_add_image_takes_precedence() {
+        let mut user_textures = EguiUserTextures::default();
+        let [first, second] = [1, 2].map(Handle::<Image>::weak_from_u128);
+
+        // Added explicitly after being registered automatically.
+        let handle = user_textures.image_handle(first.clone());
+        user_textures.frame_image(&first);
+        assert_eq!(user_textures.add_image(first.clone()), handle.texture_id());
+        drop(handle);
+
+        // Registered automatically after being added explicitly.
+        let texture_id = user_textures.add_image(second.clone());
+        drop(user_textures.image_handle(second.clone()));
+        assert_eq!(user_textures.frame_image(&second), texture_id);
+
+        assert!(!user_textures.free_unused_textures());
+        assert!(!user_textures.free_unused_textures());
+        assert!(user_textures.image_id(&first).is_some());
+        assert_eq!(user_textures.image_id(&second), Some(texture_id));
+    }
+
     #[test]
     fn test_headless_mode() {
         App::new()
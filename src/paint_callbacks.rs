@@ -0,0 +1,394 @@
+use crate::egui_node::{EguiBevyPaintCallback, EguiBevyPaintCallbackImpl, EguiPaintCallbackTarget};
+use bevy_ecs::{prelude::*, world::World};
+use bevy_render::{
+    extract_resource::ExtractResource,
+    render_phase::TrackedRenderPass,
+    render_resource::CommandEncoder,
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+};
+use bevy_utils::HashMap;
+use std::{
+    any::{Any, TypeId},
+    sync::Arc,
+};
+
+type PaintCallbackAdapter =
+    dyn Fn(&Arc<dyn Any + Send + Sync>) -> Option<EguiBevyPaintCallback> + Send + Sync;
+
+/// Converts paint callbacks that weren't created with [`EguiBevyPaintCallback::new_paint_callback`]
+/// into the ones the Egui render node can execute.
+///
+/// Callbacks created with [`EguiBevyPaintCallback::new_paint_callback`] and
+/// [`EguiWgpuCallback::new_paint_callback`] are supported out of the box. Callbacks that
+/// aren't supported by any adapter are skipped with a warning.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{egui_node::EguiBevyPaintCallback, paint_callbacks::EguiPaintCallbackRegistry};
+/// # use bevy_egui::egui_node::{EguiBevyPaintCallbackImpl, EguiPaintCallbackTarget};
+/// # use bevy::render::render_phase::TrackedRenderPass;
+/// # struct ThirdPartyCallback;
+/// # struct ThirdPartyAdapter(std::sync::Arc<ThirdPartyCallback>);
+/// # impl EguiBevyPaintCallbackImpl for ThirdPartyAdapter {
+/// #     fn update(&self, _: bevy_egui::egui::PaintCallbackInfo, _: Entity, _: &EguiPaintCallbackTarget, _: &mut World) {}
+/// #     fn render<'pass>(&self, _: bevy_egui::egui::PaintCallbackInfo, _: &mut TrackedRenderPass<'pass>, _: Entity, _: &EguiPaintCallbackTarget, _: &'pass World) {}
+/// # }
+///
+/// fn register_adapter(mut registry: ResMut<EguiPaintCallbackRegistry>) {
+///     registry.register(|callback: std::sync::Arc<ThirdPartyCallback>| {
+///         EguiBevyPaintCallback::new(ThirdPartyAdapter(callback))
+///     });
+/// }
+/// ```
+#[derive(Resource, Clone)]
+pub struct EguiPaintCallbackRegistry {
+    adapters: Vec<Arc<PaintCallbackAdapter>>,
+}
+
+impl Default for EguiPaintCallbackRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            adapters: Vec::new(),
+        };
+        registry.register(|callback: Arc<EguiWgpuCallback>| {
+            EguiBevyPaintCallback::new(EguiWgpuCallbackAdapter(callback))
+        });
+        registry
+    }
+}
+
+impl ExtractResource for EguiPaintCallbackRegistry {
+    type Source = Self;
+
+    fn extract_resource(source: &Self::Source) -> Self {
+        source.clone()
+    }
+}
+
+impl EguiPaintCallbackRegistry {
+    /// Registers an adapter for paint callbacks of the type `T`.
+    ///
+    /// Adapters are tried in the order of registration.
+    pub fn register<T, F>(&mut self, adapter: F) -> &mut Self
+    where
+        T: Any + Send + Sync,
+        F: Fn(Arc<T>) -> EguiBevyPaintCallback + Send + Sync + 'static,
+    {
+        self.adapters.push(Arc::new(move |callback| {
+            let callback = callback.clone().downcast::<T>().ok()?;
+            Some(adapter(callback))
+        }));
+        self
+    }
+
+    /// Converts a paint callback, returns [`None`] if it isn't supported by any of the adapters.
+    pub fn adapt(
+        &self,
+        callback: Arc<dyn Any + Send + Sync>,
+    ) -> Option<Arc<EguiBevyPaintCallback>> {
+        let callback = match callback.downcast::<EguiBevyPaintCallback>() {
+            Ok(callback) => return Some(callback),
+            Err(callback) => callback,
+        };
+        self.adapters
+            .iter()
+            .find_map(|adapter| adapter(&callback))
+            .map(Arc::new)
+    }
+}
+
+/// Typed storage for the GPU state of paint callbacks, analogous to `egui_wgpu::CallbackResources`.
+///
+/// Is a render world resource that holds a single value per type. Callbacks can fill it in
+/// [`EguiBevyPaintCallbackImpl::update`] and bind the stored resources in
+/// [`EguiBevyPaintCallbackImpl::render`], as they live longer than the render pass.
+#[derive(Resource, Default)]
+pub struct EguiPaintCallbackResources(HashMap<TypeId, Box<dyn Any + Send + Sync>>);
+
+impl EguiPaintCallbackResources {
+    /// Inserts a value, returns the previous value of the same type.
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) -> Option<T> {
+        self.0
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+
+    /// Returns a reference to the value of the type `T`.
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.0.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    /// Returns a mutable reference to the value of the type `T`.
+    pub fn get_mut<T: Any + Send + Sync>(&mut self) -> Option<&mut T> {
+        self.0.get_mut(&TypeId::of::<T>())?.downcast_mut()
+    }
+
+    /// Returns a mutable reference to the value of the type `T`, inserting it with `f` if it's
+    /// missing.
+    pub fn get_or_insert_with<T: Any + Send + Sync>(&mut self, f: impl FnOnce() -> T) -> &mut T {
+        self.0
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(f()))
+            .downcast_mut()
+            .expect("values are stored under the type id of their type")
+    }
+
+    /// Removes the value of the type `T`.
+    pub fn remove<T: Any + Send + Sync>(&mut self) -> Option<T> {
+        self.0
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+}
+
+/// Paint callback API that mirrors `egui_wgpu::CallbackTrait`, which makes porting widgets
+/// written for `egui-wgpu` straightforward.
+///
+/// `egui_wgpu::Callback` itself can't be executed by Bevy, as the crates depend on different
+/// `wgpu` versions.
+pub trait EguiWgpuCallbackTrait: Send + Sync {
+    /// Is called before the Egui render pass starts, can be used to create or update
+    /// GPU resources.
+    fn prepare(
+        &self,
+        device: &RenderDevice,
+        queue: &RenderQueue,
+        info: &egui::PaintCallbackInfo,
+        target: &EguiPaintCallbackTarget,
+        callback_resources: &mut EguiPaintCallbackResources,
+    ) {
+        let _ = (device, queue, info, target, callback_resources);
+    }
+
+    /// Is called after [`EguiWgpuCallbackTrait::prepare`], can be used to record commands that
+    /// have to be executed before the Egui render pass.
+    fn finish_prepare(
+        &self,
+        device: &RenderDevice,
+        queue: &RenderQueue,
+        egui_encoder: &mut CommandEncoder,
+        callback_resources: &EguiPaintCallbackResources,
+    ) {
+        let _ = (device, queue, egui_encoder, callback_resources);
+    }
+
+    /// Paints the callback into the Egui render pass.
+    ///
+    /// The viewport is already set to the callback's rect, the scissor rect is set to its
+    /// clip rect.
+    fn paint<'pass>(
+        &self,
+        info: egui::PaintCallbackInfo,
+        render_pass: &mut TrackedRenderPass<'pass>,
+        callback_resources: &'pass EguiPaintCallbackResources,
+    );
+
+    /// Returns `true` if the callback needs a depth attachment,
+    /// see [`EguiBevyPaintCallbackImpl::depth_attachment`].
+    fn depth_attachment(&self) -> bool {
+        false
+    }
+}
+
+/// A paint callback implemented with [`EguiWgpuCallbackTrait`], analogous to `egui_wgpu::Callback`.
+pub struct EguiWgpuCallback(Box<dyn EguiWgpuCallbackTrait>);
+
+impl EguiWgpuCallback {
+    /// Creates a new [`egui::epaint::PaintCallback`] from a callback trait instance.
+    pub fn new_paint_callback<T>(rect: egui::Rect, callback: T) -> egui::epaint::PaintCallback
+    where
+        T: EguiWgpuCallbackTrait + 'static,
+    {
+        egui::epaint::PaintCallback {
+            rect,
+            callback: Arc::new(Self(Box::new(callback))),
+        }
+    }
+}
+
+struct EguiWgpuCallbackAdapter(Arc<EguiWgpuCallback>);
+
+impl EguiBevyPaintCallbackImpl for EguiWgpuCallbackAdapter {
+    fn depth_attachment(&self) -> bool {
+        let EguiWgpuCallback(callback) = self.0.as_ref();
+        callback.depth_attachment()
+    }
+
+    fn update(
+        &self,
+        info: egui::PaintCallbackInfo,
+        _window_entity: Entity,
+        target: &EguiPaintCallbackTarget,
+        world: &mut World,
+    ) {
+        let EguiWgpuCallback(callback) = self.0.as_ref();
+        world.resource_scope(
+            |world, mut callback_resources: Mut<EguiPaintCallbackResources>| {
+                let (Some(device), Some(queue)) = (
+                    world.get_resource::<RenderDevice>(),
+                    world.get_resource::<RenderQueue>(),
+                ) else {
+                    return;
+                };
+                callback.prepare(device, queue, &info, target, &mut callback_resources);
+            },
+        );
+    }
+
+    fn prepare_render<'w>(
+        &self,
+        _info: egui::PaintCallbackInfo,
+        render_context: &mut RenderContext<'w>,
+        _window_entity: Entity,
+        _target: &EguiPaintCallbackTarget,
+        world: &'w World,
+    ) {
+        let (Some(queue), Some(callback_resources)) = (
+            world.get_resource::<RenderQueue>(),
+            world.get_resource::<EguiPaintCallbackResources>(),
+        ) else {
+            return;
+        };
+        let EguiWgpuCallback(callback) = self.0.as_ref();
+        let device = render_context.render_device().clone();
+        callback.finish_prepare(
+            &device,
+            queue,
+            render_context.command_encoder(),
+            callback_resources,
+        );
+    }
+
+    fn render<'pass>(
+        &self,
+        info: egui::PaintCallbackInfo,
+        render_pass: &mut TrackedRenderPass<'pass>,
+        _window_entity: Entity,
+        _target: &EguiPaintCallbackTarget,
+        world: &'pass World,
+    ) {
+        let Some(callback_resources) = world.get_resource::<EguiPaintCallbackResources>() else {
+            return;
+        };
+        let EguiWgpuCallback(callback) = self.0.as_ref();
+        callback.paint(info, render_pass, callback_resources);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestCallback(bool);
+
+    impl EguiBevyPaintCallbackImpl for TestCallback {
+        fn depth_attachment(&self) -> bool {
+            self.0
+        }
+
+        fn update(
+            &self,
+            _info: egui::PaintCallbackInfo,
+            _window_entity: Entity,
+            _target: &EguiPaintCallbackTarget,
+            _world: &mut World,
+        ) {
+        }
+
+        fn render<'pass>(
+            &self,
+            _info: egui::PaintCallbackInfo,
+            _render_pass: &mut TrackedRenderPass<'pass>,
+            _window_entity: Entity,
+            _target: &EguiPaintCallbackTarget,
+            _world: &'pass World,
+        ) {
+        }
+    }
+
+    struct TestWgpuCallback;
+
+    impl EguiWgpuCallbackTrait for TestWgpuCallback {
+        fn paint<'pass>(
+            &self,
+            _info: egui::PaintCallbackInfo,
+            _render_pass: &mut TrackedRenderPass<'pass>,
+            _callback_resources: &'pass EguiPaintCallbackResources,
+        ) {
+        }
+
+        fn depth_attachment(&self) -> bool {
+            true
+        }
+    }
+
+    struct ThirdPartyCallback(bool);
+
+    fn paint_callback_depth(callback: &EguiBevyPaintCallback) -> bool {
+        callback.cb().depth_attachment()
+    }
+
+    #[test]
+    fn test_registry_passes_bevy_callbacks_through() {
+        let registry = EguiPaintCallbackRegistry::default();
+        let callback =
+            EguiBevyPaintCallback::new_paint_callback(egui::Rect::EVERYTHING, TestCallback(false))
+                .callback;
+        let adapted = registry.adapt(callback.clone()).unwrap();
+        assert!(Arc::ptr_eq(
+            &(adapted as Arc<dyn Any + Send + Sync>),
+            &callback
+        ));
+    }
+
+    #[test]
+    fn test_registry_adapts_wgpu_callbacks() {
+        let registry = EguiPaintCallbackRegistry::default();
+        let callback =
+            EguiWgpuCallback::new_paint_callback(egui::Rect::EVERYTHING, TestWgpuCallback).callback;
+        let adapted = registry.adapt(callback).unwrap();
+        assert!(paint_callback_depth(&adapted));
+    }
+
+    #[test]
+    fn test_registry_skips_unsupported_callbacks() {
+        let registry = EguiPaintCallbackRegistry::default();
+        assert!(registry.adapt(Arc::new(ThirdPartyCallback(true))).is_none());
+    }
+
+    #[test]
+    fn test_registry_tries_adapters_in_order() {
+        let mut registry = EguiPaintCallbackRegistry::default();
+        registry
+            .register(|_: Arc<u32>| EguiBevyPaintCallback::new(TestCallback(false)))
+            .register(|callback: Arc<ThirdPartyCallback>| {
+                EguiBevyPaintCallback::new(TestCallback(callback.0))
+            })
+            .register(|_: Arc<ThirdPartyCallback>| EguiBevyPaintCallback::new(TestCallback(false)));
+        let adapted = registry.adapt(Arc::new(ThirdPartyCallback(true))).unwrap();
+        assert!(paint_callback_depth(&adapted));
+    }
+
+    #[test]
+    fn test_callback_resources() {
+        let mut resources = EguiPaintCallbackResources::default();
+        assert_eq!(resources.get::<u32>(), None);
+        assert_eq!(resources.insert(1u32), None);
+        assert_eq!(resources.insert(2u32), Some(1));
+        assert_eq!(resources.insert("value"), None);
+        assert_eq!(resources.get::<u32>(), Some(&2));
+        assert_eq!(resources.get::<&str>(), Some(&"value"));
+        assert_eq!(resources.get::<u64>(), None);
+
+        *resources.get_mut::<u32>().unwrap() += 1;
+        assert_eq!(resources.get::<u32>(), Some(&3));
+        assert_eq!(*resources.get_or_insert_with(|| 10u32), 3);
+        assert_eq!(*resources.get_or_insert_with(|| 10u64), 10);
+
+        assert_eq!(resources.remove::<u32>(), Some(3));
+        assert_eq!(resources.remove::<u32>(), None);
+        assert_eq!(resources.get::<u64>(), Some(&10));
+    }
+}
@@ -1,14 +1,21 @@
 use crate::{
+    egui_atlas_node::{EguiAtlasNode, EguiAtlasPass},
+    egui_camera_node::{
+        EguiCameraNode, EguiCameraPass, EguiViewNode, EguiViewPass, EguiViewTarget,
+    },
     egui_node::{EguiNode, EguiPipeline, EguiPipelineKey},
     egui_render_to_texture_node::{EguiRenderToTextureNode, EguiRenderToTexturePass},
-    EguiManagedTextures, EguiRenderToTextureHandle, EguiSettings, EguiUserTextures,
-    RenderTargetSize,
+    gpu_textures::EguiGpuTexturesPass,
+    EguiCameraContext, EguiContext, EguiError, EguiManagedTextures, EguiRenderOrder,
+    EguiRenderToTextureAtlas, EguiRenderToTextureAtlasRegion, EguiRenderToTextureHandle,
+    EguiSettings, EguiShader, EguiUserTextures, RenderTargetSize,
 };
 use bevy_asset::prelude::*;
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::{prelude::*, system::SystemParam};
-use bevy_math::Vec2;
+use bevy_math::Vec4;
 use bevy_render::{
+    camera::{CameraRenderGraph, ExtractedCamera, ManualTextureViews},
     extract_resource::ExtractResource,
     render_asset::RenderAssets,
     render_graph::{RenderGraph, RenderLabel},
@@ -16,13 +23,35 @@ use bevy_render::{
         BindGroup, BindGroupEntry, BindingResource, BufferId, CachedRenderPipelineId,
         DynamicUniformBuffer, PipelineCache, SpecializedRenderPipelines,
     },
-    renderer::{RenderDevice, RenderQueue},
+    renderer::{RenderAdapter, RenderDevice, RenderQueue},
     texture::{GpuImage, Image},
-    view::ExtractedWindows,
+    view::{ExtractedWindows, ViewTarget},
     Extract,
 };
 use bevy_utils::HashMap;
 use bevy_window::Window;
+use std::sync::{Arc, Mutex};
+
+/// Errors reported by the render world, they are sent as [`EguiError`] events in the main world.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct EguiRenderErrors(Arc<Mutex<Vec<EguiError>>>);
+
+impl EguiRenderErrors {
+    /// Queues an error to be sent as an event in the main world.
+    pub(crate) fn push(&self, error: EguiError) {
+        if let Ok(mut errors) = self.0.lock() {
+            errors.push(error);
+        }
+    }
+
+    /// Takes all the queued errors.
+    pub(crate) fn drain(&self) -> Vec<EguiError> {
+        self.0
+            .lock()
+            .map(|mut errors| std::mem::take(&mut *errors))
+            .unwrap_or_default()
+    }
+}
 
 /// Extracted Egui settings.
 #[derive(Resource, Deref, DerefMut, Default)]
@@ -84,9 +113,11 @@ impl ExtractedEguiTextures<'_> {
     }
 }
 
-/// Sets up the pipeline for newly created windows.
+/// Sets up the pipeline for newly created windows, and for the ones that got their
+/// [`EguiContext`] re-added.
+#[allow(clippy::type_complexity)]
 pub fn setup_new_windows_render_system(
-    windows: Extract<Query<Entity, Added<Window>>>,
+    windows: Extract<Query<Entity, (With<Window>, Or<(Added<Window>, Added<EguiContext>)>)>>,
     mut render_graph: ResMut<RenderGraph>,
 ) {
     for window in windows.iter() {
@@ -94,17 +125,32 @@ pub fn setup_new_windows_render_system(
             entity_index: window.index(),
             entity_generation: window.generation(),
         };
+        // The context was added after the window, which already has its node.
+        if render_graph.get_node_state(egui_pass.clone()).is_ok() {
+            continue;
+        }
 
         let new_node = EguiNode::new(window);
 
         render_graph.add_node(egui_pass.clone(), new_node);
 
-        render_graph.add_node_edge(bevy_render::graph::CameraDriverLabel, egui_pass);
+        render_graph.add_node_edge(bevy_render::graph::CameraDriverLabel, egui_pass.clone());
+        render_graph.add_node_edge(EguiGpuTexturesPass, egui_pass);
     }
 }
-/// Sets up the pipeline for newly created Render to texture entities.
+/// Sets up the pipeline for newly created Render to texture entities, and for the ones that got
+/// their [`EguiContext`] re-added.
+#[allow(clippy::type_complexity)]
 pub fn setup_new_rtt_render_system(
-    render_to_texture_targets: Extract<Query<Entity, Added<EguiRenderToTextureHandle>>>,
+    render_to_texture_targets: Extract<
+        Query<
+            Entity,
+            (
+                With<EguiRenderToTextureHandle>,
+                Or<(Added<EguiRenderToTextureHandle>, Added<EguiContext>)>,
+            ),
+        >,
+    >,
     mut render_graph: ResMut<RenderGraph>,
 ) {
     for render_to_texture_target in render_to_texture_targets.iter() {
@@ -112,6 +158,9 @@ pub fn setup_new_rtt_render_system(
             entity_index: render_to_texture_target.index(),
             entity_generation: render_to_texture_target.generation(),
         };
+        if render_graph.get_node_state(egui_rtt_pass.clone()).is_ok() {
+            continue;
+        }
 
         let new_node = EguiRenderToTextureNode::new(render_to_texture_target);
 
@@ -121,6 +170,228 @@ pub fn setup_new_rtt_render_system(
     }
 }
 
+/// Sets up the pipeline for newly created texture atlases.
+pub fn setup_new_atlases_render_system(
+    atlases: Extract<Query<Entity, Added<EguiRenderToTextureAtlas>>>,
+    mut render_graph: ResMut<RenderGraph>,
+) {
+    for atlas in atlases.iter() {
+        let egui_atlas_pass = EguiAtlasPass {
+            entity_index: atlas.index(),
+            entity_generation: atlas.generation(),
+        };
+
+        let new_node = EguiAtlasNode::new(atlas);
+
+        render_graph.add_node(egui_atlas_pass.clone(), new_node);
+
+        render_graph.add_node_edge(egui_atlas_pass, bevy_render::graph::CameraDriverLabel);
+    }
+}
+
+/// Sets up the pipeline for newly created camera contexts, and for the ones that got their
+/// [`EguiContext`] re-added.
+#[allow(clippy::type_complexity)]
+pub fn setup_new_camera_contexts_render_system(
+    camera_contexts: Extract<
+        Query<
+            Entity,
+            (
+                With<EguiCameraContext>,
+                Or<(Added<EguiCameraContext>, Added<EguiContext>)>,
+            ),
+        >,
+    >,
+    mut render_graph: ResMut<RenderGraph>,
+) {
+    for camera in camera_contexts.iter() {
+        let egui_camera_pass = EguiCameraPass {
+            entity_index: camera.index(),
+            entity_generation: camera.generation(),
+        };
+        if render_graph
+            .get_node_state(egui_camera_pass.clone())
+            .is_ok()
+        {
+            continue;
+        }
+
+        let new_node = EguiCameraNode::new(camera);
+
+        render_graph.add_node(egui_camera_pass.clone(), new_node);
+
+        render_graph.add_node_edge(
+            bevy_render::graph::CameraDriverLabel,
+            egui_camera_pass.clone(),
+        );
+        render_graph.add_node_edge(EguiGpuTexturesPass, egui_camera_pass);
+    }
+}
+
+/// Removes render graph nodes of despawned windows, render to texture targets, texture atlases,
+/// camera contexts, and entities that had their [`EguiContext`] removed. Dropping a node also frees its vertex
+/// and index buffers.
+pub fn teardown_removed_contexts_render_system(
+    mut removed_contexts: Extract<RemovedComponents<EguiContext>>,
+    mut removed_windows: Extract<RemovedComponents<Window>>,
+    mut removed_render_to_texture_targets: Extract<RemovedComponents<EguiRenderToTextureHandle>>,
+    mut removed_atlases: Extract<RemovedComponents<EguiRenderToTextureAtlas>>,
+    mut removed_camera_contexts: Extract<RemovedComponents<EguiCameraContext>>,
+    mut render_graph: ResMut<RenderGraph>,
+) {
+    let removed_entities = removed_contexts
+        .read()
+        .chain(removed_windows.read())
+        .chain(removed_render_to_texture_targets.read())
+        .chain(removed_atlases.read())
+        .chain(removed_camera_contexts.read());
+    for entity in removed_entities {
+        // An entity has one of these nodes, we don't care if the others don't exist.
+        let _ = render_graph.remove_node(EguiPass {
+            entity_index: entity.index(),
+            entity_generation: entity.generation(),
+        });
+        let _ = render_graph.remove_node(EguiRenderToTexturePass {
+            entity_index: entity.index(),
+            entity_generation: entity.generation(),
+        });
+        let _ = render_graph.remove_node(EguiAtlasPass {
+            entity_index: entity.index(),
+            entity_generation: entity.generation(),
+        });
+        let _ = render_graph.remove_node(EguiCameraPass {
+            entity_index: entity.index(),
+            entity_generation: entity.generation(),
+        });
+        remove_view_nodes(&mut render_graph, entity);
+    }
+}
+
+/// Moves the Egui passes of window and camera contexts according to their [`EguiRenderOrder`].
+///
+/// Contexts that got their [`EguiContext`] re-added are moved again, as their nodes were
+/// re-created by the setup systems.
+#[allow(clippy::type_complexity)]
+pub fn update_render_order_render_system(
+    changed_orders: Extract<Query<Entity, Changed<EguiRenderOrder>>>,
+    mut removed_orders: Extract<RemovedComponents<EguiRenderOrder>>,
+    added_contexts: Extract<Query<Entity, (Added<EguiContext>, With<EguiRenderOrder>)>>,
+    render_orders: Extract<Query<&EguiRenderOrder>>,
+    contexts: Extract<Query<(Has<Window>, Has<EguiCameraContext>)>>,
+    camera_render_graphs: Extract<Query<&CameraRenderGraph>>,
+    mut render_graph: ResMut<RenderGraph>,
+) {
+    let entities = changed_orders
+        .iter()
+        .chain(removed_orders.read())
+        .chain(added_contexts.iter());
+    for entity in entities {
+        // The entity is despawned, its nodes are removed by `teardown_removed_contexts_render_system`.
+        let Ok((is_window, is_camera_context)) = contexts.get(entity) else {
+            continue;
+        };
+        let render_order = render_orders.get(entity).copied().unwrap_or_default();
+
+        remove_view_nodes(&mut render_graph, entity);
+
+        if let EguiRenderOrder::InCamera {
+            camera,
+            after,
+            before,
+        } = render_order
+        {
+            if !is_window && !is_camera_context {
+                continue;
+            }
+            let _ = render_graph.remove_node(EguiPass {
+                entity_index: entity.index(),
+                entity_generation: entity.generation(),
+            });
+            let _ = render_graph.remove_node(EguiCameraPass {
+                entity_index: entity.index(),
+                entity_generation: entity.generation(),
+            });
+
+            let Ok(camera_render_graph) = camera_render_graphs.get(camera) else {
+                bevy_log::warn!(
+                    "`EguiRenderOrder::InCamera` of {entity:?} refers to {camera:?}, which isn't a camera"
+                );
+                continue;
+            };
+            // The camera graph runs inside `CameraDriverLabel`, before `EguiGpuTexturesPass`,
+            // so converted GPU textures show the last frame here too.
+            let Some(sub_graph) = render_graph.get_sub_graph_mut(**camera_render_graph) else {
+                continue;
+            };
+            let egui_view_pass = EguiViewPass {
+                entity_index: entity.index(),
+                entity_generation: entity.generation(),
+            };
+            sub_graph.add_node(
+                egui_view_pass.clone(),
+                EguiViewNode::with_source(EguiViewTarget { camera }, entity),
+            );
+            let edges = sub_graph
+                .try_add_node_edge(after, egui_view_pass.clone())
+                .and_then(|_| sub_graph.try_add_node_edge(egui_view_pass.clone(), before));
+            if let Err(err) = edges {
+                bevy_log::warn!(
+                    "Failed to order the Egui pass of {entity:?} in the graph of {camera:?}: {err}"
+                );
+                let _ = sub_graph.remove_node(egui_view_pass);
+            }
+            continue;
+        }
+
+        let egui_pass = if is_window {
+            EguiPass {
+                entity_index: entity.index(),
+                entity_generation: entity.generation(),
+            }
+            .intern()
+        } else if is_camera_context {
+            EguiCameraPass {
+                entity_index: entity.index(),
+                entity_generation: entity.generation(),
+            }
+            .intern()
+        } else {
+            continue;
+        };
+        if render_graph.get_node_state(egui_pass).is_err() {
+            if is_window {
+                render_graph.add_node(egui_pass, EguiNode::new(entity));
+            } else {
+                render_graph.add_node(egui_pass, EguiCameraNode::new(entity));
+            }
+        }
+
+        let camera_driver = bevy_render::graph::CameraDriverLabel.intern();
+        let gpu_textures = EguiGpuTexturesPass.intern();
+        let _ = render_graph.remove_node_edge(camera_driver, egui_pass);
+        let _ = render_graph.remove_node_edge(egui_pass, camera_driver);
+        let _ = render_graph.remove_node_edge(gpu_textures, egui_pass);
+        match render_order {
+            // GPU textures are converted after cameras, passes before them show the last frame.
+            EguiRenderOrder::BeforeCameras => render_graph.add_node_edge(egui_pass, camera_driver),
+            _ => {
+                render_graph.add_node_edge(camera_driver, egui_pass);
+                render_graph.add_node_edge(gpu_textures, egui_pass);
+            }
+        }
+    }
+}
+
+/// Removes the Egui pass of an entity from all the camera render graphs.
+fn remove_view_nodes(render_graph: &mut RenderGraph, entity: Entity) {
+    for (_, sub_graph) in render_graph.iter_sub_graphs_mut() {
+        let _ = sub_graph.remove_node(EguiViewPass {
+            entity_index: entity.index(),
+            entity_generation: entity.generation(),
+        });
+    }
+}
+
 /// Describes the transform buffer.
 #[derive(Resource, Default)]
 pub struct EguiTransforms {
@@ -132,36 +403,61 @@ pub struct EguiTransforms {
     pub bind_group: Option<(BufferId, BindGroup)>,
 }
 
-/// Scale and translation for rendering Egui shapes. Is needed to transform Egui coordinates from
-/// the screen space with the center at (0, 0) to the normalised viewport space.
-#[derive(encase::ShaderType, Default)]
-pub struct EguiTransform {
-    /// Is affected by window size and [`EguiSettings::scale_factor`].
-    pub scale: Vec2,
-    /// Normally equals `Vec2::new(-1.0, 1.0)`.
-    pub translation: Vec2,
-}
+pub use transform::EguiTransform;
 
-impl EguiTransform {
-    /// Calculates the transform from window size and scale factor.
-    pub fn from_render_target_size(
-        render_target_size: RenderTargetSize,
-        scale_factor: f32,
-    ) -> Self {
-        EguiTransform {
-            scale: Vec2::new(
-                2.0 / (render_target_size.width() / scale_factor),
-                -2.0 / (render_target_size.height() / scale_factor),
-            ),
-            translation: Vec2::new(-1.0, 1.0),
+// The `ShaderType` derive generates a `check` function per field that recent compilers report
+// as never used, the lint can only be allowed for the module the generated code is in.
+#[allow(dead_code)]
+mod transform {
+    use crate::RenderTargetSize;
+    use bevy_math::{Vec2, Vec4};
+
+    /// Scale and translation for rendering Egui shapes. Is needed to transform Egui coordinates from
+    /// the screen space with the center at (0, 0) to the normalised viewport space.
+    #[derive(encase::ShaderType, Default)]
+    pub struct EguiTransform {
+        /// Is affected by window size and [`EguiSettings::scale_factor`](crate::EguiSettings::scale_factor).
+        pub scale: Vec2,
+        /// Normally equals `Vec2::new(-1.0, 1.0)`.
+        pub translation: Vec2,
+        /// Multiplier for colors written to HDR targets, is derived from
+        /// [`EguiSettings::hdr_paper_white_nits`](crate::EguiSettings::hdr_paper_white_nits).
+        pub hdr_brightness: f32,
+        /// Parameters of a custom fragment shader, see [`EguiShader::params`](crate::EguiShader::params).
+        pub shader_params: Vec4,
+    }
+
+    impl EguiTransform {
+        /// Calculates the transform from window size and scale factor.
+        pub fn from_render_target_size(
+            render_target_size: RenderTargetSize,
+            scale_factor: f32,
+        ) -> Self {
+            EguiTransform {
+                scale: Vec2::new(
+                    2.0 / (render_target_size.width() / scale_factor),
+                    -2.0 / (render_target_size.height() / scale_factor),
+                ),
+                translation: Vec2::new(-1.0, 1.0),
+                hdr_brightness: 1.0,
+                shader_params: Vec4::ZERO,
+            }
         }
     }
 }
 
+/// Brightness of `1.0` in scRGB, which HDR float targets are assumed to use.
+pub(crate) const SCRGB_REFERENCE_WHITE_NITS: f32 = 80.0;
+
 /// Prepares Egui transforms.
 pub fn prepare_egui_transforms_system(
     mut egui_transforms: ResMut<EguiTransforms>,
-    render_targets: Query<(Entity, &EguiSettings, &RenderTargetSize)>,
+    render_targets: Query<(
+        Entity,
+        &EguiSettings,
+        &RenderTargetSize,
+        Option<&EguiShader>,
+    )>,
     render_device: Res<RenderDevice>,
     render_queue: Res<RenderQueue>,
     egui_pipeline: Res<EguiPipeline>,
@@ -169,13 +465,12 @@ pub fn prepare_egui_transforms_system(
     egui_transforms.buffer.clear();
     egui_transforms.offsets.clear();
 
-    for (render_target, egui_settings, size) in render_targets.iter() {
-        let offset = egui_transforms
-            .buffer
-            .push(&EguiTransform::from_render_target_size(
-                *size,
-                egui_settings.scale_factor,
-            ));
+    for (render_target, egui_settings, size, egui_shader) in render_targets.iter() {
+        let offset = egui_transforms.buffer.push(&EguiTransform {
+            hdr_brightness: egui_settings.hdr_paper_white_nits / SCRGB_REFERENCE_WHITE_NITS,
+            shader_params: egui_shader.map_or(Vec4::ZERO, |egui_shader| egui_shader.params),
+            ..EguiTransform::from_render_target_size(*size, egui_settings.scale_factor)
+        });
         egui_transforms.offsets.insert(render_target, offset);
     }
 
@@ -243,6 +538,7 @@ pub fn queue_bind_groups_system(
 pub struct EguiPipelines(pub HashMap<Entity, CachedRenderPipelineId>);
 
 /// Queue [`EguiPipeline`] instances specialized on each window's swap chain texture format.
+#[allow(clippy::too_many_arguments)]
 pub fn queue_pipelines_system(
     mut commands: Commands,
     pipeline_cache: Res<PipelineCache>,
@@ -250,25 +546,79 @@ pub fn queue_pipelines_system(
     egui_pipeline: Res<EguiPipeline>,
     windows: Res<ExtractedWindows>,
     render_to_texture: Query<(Entity, &EguiRenderToTextureHandle)>,
+    atlases: Query<&EguiRenderToTextureAtlas>,
+    atlas_regions: Query<(Entity, &EguiRenderToTextureAtlasRegion)>,
+    cameras: Query<(Entity, &ExtractedCamera), With<EguiContext>>,
+    render_orders: Query<(Entity, &EguiRenderOrder)>,
+    view_targets: Query<&ViewTarget>,
+    contexts: Query<(Option<&EguiSettings>, Option<&EguiShader>)>,
     images: Res<RenderAssets<GpuImage>>,
+    manual_texture_views: Res<ManualTextureViews>,
+    render_adapter: Res<RenderAdapter>,
 ) {
-    let mut pipelines: HashMap<Entity, CachedRenderPipelineId> = windows
+    let mut keys: HashMap<Entity, EguiPipelineKey> = windows
         .iter()
         .filter_map(|(window_id, window)| {
-            let key = EguiPipelineKey::from_extracted_window(window)?;
-            let pipeline_id =
-                specialized_pipelines.specialize(&pipeline_cache, &egui_pipeline, key);
-            Some((*window_id, pipeline_id))
+            Some((*window_id, EguiPipelineKey::from_extracted_window(window)?))
         })
         .collect();
 
-    pipelines.extend(render_to_texture.iter().filter_map(|(entity_id, handle)| {
+    keys.extend(render_to_texture.iter().filter_map(|(entity_id, handle)| {
         let img = images.get(&handle.0)?;
-        let key = EguiPipelineKey::from_gpu_image(img);
-        let pipeline_id = specialized_pipelines.specialize(&pipeline_cache, &egui_pipeline, key);
+        Some((entity_id, EguiPipelineKey::from_gpu_image(img)))
+    }));
 
-        Some((entity_id, pipeline_id))
+    keys.extend(atlas_regions.iter().filter_map(|(entity_id, region)| {
+        let img = images.get(&atlases.get(region.atlas).ok()?.image)?;
+        Some((entity_id, EguiPipelineKey::from_gpu_image(img)))
     }));
 
+    keys.extend(cameras.iter().filter_map(|(entity_id, extracted_camera)| {
+        let key = EguiPipelineKey::from_render_target(
+            extracted_camera.target.as_ref()?,
+            &windows,
+            &images,
+            &manual_texture_views,
+        )?;
+        Some((entity_id, key))
+    }));
+
+    // Contexts rendered from inside a camera graph draw into the camera's main texture.
+    // Other contexts ignore the render order, see `update_render_order_render_system`.
+    keys.extend(
+        render_orders
+            .iter()
+            .filter(|(entity_id, _)| {
+                windows.contains_key(entity_id) || cameras.contains(*entity_id)
+            })
+            .filter_map(|(entity_id, render_order)| {
+                let EguiRenderOrder::InCamera { camera, .. } = render_order else {
+                    return None;
+                };
+                let key = EguiPipelineKey::from_view_target(view_targets.get(*camera).ok()?);
+                Some((entity_id, key))
+            }),
+    );
+
+    let pipelines = keys
+        .into_iter()
+        .map(|(entity_id, key)| {
+            let (egui_settings, egui_shader) = contexts.get(entity_id).unwrap_or((None, None));
+            let key = match egui_settings {
+                Some(egui_settings) => key.with_settings(egui_settings, &render_adapter),
+                None => key,
+            };
+            // Atlas regions are drawn directly into the atlas, in a pass shared with others.
+            let key = match atlas_regions.contains(entity_id) {
+                true => key.without_intermediate_texture(),
+                false => key,
+            };
+            let key = key.with_shader(egui_shader);
+            let pipeline_id =
+                specialized_pipelines.specialize(&pipeline_cache, &egui_pipeline, key);
+            (entity_id, pipeline_id)
+        })
+        .collect();
+
     commands.insert_resource(EguiPipelines(pipelines));
 }
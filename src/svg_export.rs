@@ -0,0 +1,862 @@
+use crate::{
+    egui_node::as_color_image, update_image_rect, EguiError, EguiFullOutput, EguiManagedTextures,
+    EguiSettings, EguiUserTextures, RenderTargetSize,
+};
+use bevy_asset::Assets;
+use bevy_ecs::prelude::*;
+use bevy_render::texture::Image;
+use bevy_utils::HashMap;
+use egui::{
+    epaint::{ClippedShape, ColorMode, Mesh, PathStroke, RectShape, TextShape, Vertex},
+    Color32, ColorImage, Pos2, Rect, Shape, Stroke, TextureId, Vec2,
+};
+use std::{fmt::Write, path::PathBuf};
+
+/// Requests exporting what an Egui context paints in the current pass as an SVG document.
+///
+/// The shapes of the pass are converted before they are tessellated: clip rects become clip
+/// paths, text is drawn with glyphs cropped from the embedded font texture, and images are
+/// embedded as PNGs. When the document is ready, it's sent as an [`EguiSvgExported`] event and
+/// the component is removed.
+///
+/// Exporting doesn't involve the GPU, so it works in headless apps too. To convert shapes
+/// outside of the plugin's schedule, call [`shapes_to_svg`].
+///
+/// ```rust
+/// use bevy::{prelude::*, window::PrimaryWindow};
+/// use bevy_egui::svg_export::EguiSvgExport;
+///
+/// fn export_system(
+///     mut commands: Commands,
+///     keys: Res<ButtonInput<KeyCode>>,
+///     window: Query<Entity, With<PrimaryWindow>>,
+/// ) {
+///     if keys.just_pressed(KeyCode::F11) {
+///         commands
+///             .entity(window.single())
+///             .insert(EguiSvgExport::save("panel.svg"));
+///     }
+/// }
+/// ```
+#[derive(Component, Clone, Debug, Default)]
+pub struct EguiSvgExport {
+    /// Path of a file to save the document to.
+    pub path: Option<PathBuf>,
+}
+
+impl EguiSvgExport {
+    /// Constructs the request to export the UI and save it to a file.
+    pub fn save(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Some(path.into()),
+        }
+    }
+}
+
+/// Is sent when the document requested with an [`EguiSvgExport`] is ready.
+#[derive(Event, Clone, Debug)]
+pub struct EguiSvgExported {
+    /// Entity of the context.
+    pub context: Entity,
+    /// SVG document.
+    pub svg: String,
+}
+
+/// Exports the shapes of contexts with an [`EguiSvgExport`].
+#[allow(clippy::type_complexity)]
+pub fn export_svg_system(
+    mut commands: Commands,
+    contexts: Query<(
+        Entity,
+        &EguiSvgExport,
+        &EguiFullOutput,
+        &EguiSettings,
+        &RenderTargetSize,
+    )>,
+    managed_textures: Res<EguiManagedTextures>,
+    user_textures: Res<EguiUserTextures>,
+    images: Res<Assets<Image>>,
+    mut exported: EventWriter<EguiSvgExported>,
+    mut egui_errors: EventWriter<EguiError>,
+) {
+    for (context, export, full_output, egui_settings, render_target_size) in contexts.iter() {
+        let Some(full_output) = full_output.as_ref() else {
+            continue;
+        };
+        let size = Vec2::new(render_target_size.width(), render_target_size.height())
+            / egui_settings.scale_factor;
+        let svg = shapes_to_svg(&full_output.shapes, size, |texture_id| match texture_id {
+            TextureId::Managed(id) => {
+                let mut color_image = managed_textures
+                    .get(&(context, id))
+                    .map(|managed_texture| managed_texture.color_image.clone());
+                // Textures of the pass are uploaded after its output is processed.
+                for (_, image_delta) in full_output
+                    .textures_delta
+                    .set
+                    .iter()
+                    .filter(|(delta_id, _)| *delta_id == texture_id)
+                {
+                    let delta_image = as_color_image(image_delta.image.clone());
+                    match (image_delta.pos, color_image.as_mut()) {
+                        (None, _) => color_image = Some(delta_image),
+                        (Some(pos), Some(color_image)) => {
+                            update_image_rect(color_image, pos, &delta_image)
+                        }
+                        (Some(_), None) => {}
+                    }
+                }
+                color_image
+            }
+            TextureId::User(id) => {
+                let image = images.get(user_textures.image(id)?)?;
+                let rgba = image.clone().try_into_dynamic().ok()?.to_rgba8();
+                Some(ColorImage::from_rgba_unmultiplied(
+                    [rgba.width() as usize, rgba.height() as usize],
+                    rgba.as_raw(),
+                ))
+            }
+        });
+
+        if let Some(path) = &export.path {
+            if let Err(err) = std::fs::write(path, &svg) {
+                let error = EguiError::SvgNotSaved {
+                    target: context,
+                    path: path.clone(),
+                };
+                bevy_log::error!("{error}: {err}");
+                egui_errors.send(error);
+            }
+        }
+        commands.entity(context).remove::<EguiSvgExport>();
+        exported.send(EguiSvgExported { context, svg });
+    }
+}
+
+/// Converts shapes painted by Egui into an SVG document.
+///
+/// `size` is the size of the document in points, usually the size of the screen rect.
+/// `texture` returns the images of the textures the shapes use, the font texture is
+/// [`TextureId::default`]. Shapes using textures it doesn't return are filled with their tint.
+/// Paint callbacks are skipped.
+pub fn shapes_to_svg(
+    shapes: &[ClippedShape],
+    size: Vec2,
+    mut texture: impl FnMut(TextureId) -> Option<ColorImage>,
+) -> String {
+    let mut writer = SvgWriter {
+        texture: &mut texture,
+        defs: String::new(),
+        body: String::new(),
+        textures: HashMap::default(),
+        tints: HashMap::default(),
+        blurs: HashMap::default(),
+        clip_paths: 0,
+    };
+    let screen_rect = Rect::from_min_size(Pos2::ZERO, size);
+    let mut current_clip_rect = None;
+    for ClippedShape { clip_rect, shape } in shapes {
+        let clip_rect = clip_rect.intersect(screen_rect);
+        if current_clip_rect != Some(clip_rect) {
+            if current_clip_rect.is_some() {
+                writer.body.push_str("</g>\n");
+            }
+            let id = writer.clip_path(clip_rect);
+            let _ = writeln!(writer.body, r#"<g clip-path="url(#clip{id})">"#);
+            current_clip_rect = Some(clip_rect);
+        }
+        writer.shape(shape);
+    }
+    if current_clip_rect.is_some() {
+        writer.body.push_str("</g>\n");
+    }
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">\n<defs>\n{defs}</defs>\n{body}</svg>\n",
+        width = num(size.x),
+        height = num(size.y),
+        defs = writer.defs,
+        body = writer.body,
+    )
+}
+
+struct SvgWriter<'a> {
+    texture: &'a mut dyn FnMut(TextureId) -> Option<ColorImage>,
+    defs: String,
+    body: String,
+    /// Ids and sizes of the embedded textures, `None` for missing ones.
+    textures: HashMap<TextureId, Option<(usize, [usize; 2])>>,
+    /// Filters tinting the font texture, by color.
+    tints: HashMap<Color32, usize>,
+    /// Filters blurring shadows, by the bits of the blur width.
+    blurs: HashMap<u32, usize>,
+    clip_paths: usize,
+}
+
+impl SvgWriter<'_> {
+    fn shape(&mut self, shape: &Shape) {
+        match shape {
+            Shape::Noop | Shape::Callback(_) => {}
+            Shape::Vec(shapes) => {
+                for shape in shapes {
+                    self.shape(shape);
+                }
+            }
+            Shape::Circle(circle) => {
+                let _ = writeln!(
+                    self.body,
+                    r#"<circle cx="{}" cy="{}" r="{}"{}{}/>"#,
+                    num(circle.center.x),
+                    num(circle.center.y),
+                    num(circle.radius),
+                    paint("fill", circle.fill),
+                    stroke(circle.stroke),
+                );
+            }
+            Shape::Ellipse(ellipse) => {
+                let _ = writeln!(
+                    self.body,
+                    r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}"{}{}/>"#,
+                    num(ellipse.center.x),
+                    num(ellipse.center.y),
+                    num(ellipse.radius.x),
+                    num(ellipse.radius.y),
+                    paint("fill", ellipse.fill),
+                    stroke(ellipse.stroke),
+                );
+            }
+            Shape::LineSegment { points, stroke } => {
+                self.path(points, false, Color32::TRANSPARENT, stroke);
+            }
+            Shape::Path(path) => {
+                self.path(&path.points, path.closed, path.fill, &path.stroke);
+            }
+            Shape::Rect(rect) => self.rect(rect),
+            Shape::Text(text) => self.text(text),
+            Shape::Mesh(mesh) => self.mesh(mesh),
+            Shape::QuadraticBezier(bezier) => {
+                let [p0, p1, p2] = bezier.points;
+                let data = format!(
+                    "M{} {} Q{} {} {} {}",
+                    num(p0.x),
+                    num(p0.y),
+                    num(p1.x),
+                    num(p1.y),
+                    num(p2.x),
+                    num(p2.y),
+                );
+                let bounds = Rect::from_points(&bezier.points);
+                self.path_data(&data, bounds, bezier.closed, bezier.fill, &bezier.stroke);
+            }
+            Shape::CubicBezier(bezier) => {
+                let [p0, p1, p2, p3] = bezier.points;
+                let data = format!(
+                    "M{} {} C{} {} {} {} {} {}",
+                    num(p0.x),
+                    num(p0.y),
+                    num(p1.x),
+                    num(p1.y),
+                    num(p2.x),
+                    num(p2.y),
+                    num(p3.x),
+                    num(p3.y),
+                );
+                let bounds = Rect::from_points(&bezier.points);
+                self.path_data(&data, bounds, bezier.closed, bezier.fill, &bezier.stroke);
+            }
+        }
+    }
+
+    fn path(&mut self, points: &[Pos2], closed: bool, fill: Color32, stroke: &PathStroke) {
+        let mut data = String::new();
+        for (i, point) in points.iter().enumerate() {
+            let command = if i == 0 { 'M' } else { 'L' };
+            let _ = write!(data, "{command}{} {} ", num(point.x), num(point.y));
+        }
+        if !data.is_empty() {
+            let bounds = Rect::from_points(points);
+            self.path_data(data.trim_end(), bounds, closed, fill, stroke);
+        }
+    }
+
+    fn path_data(
+        &mut self,
+        data: &str,
+        bounds: Rect,
+        closed: bool,
+        fill: Color32,
+        path_stroke: &PathStroke,
+    ) {
+        let close = if closed { " Z" } else { "" };
+        // Open paths aren't filled by Egui.
+        let fill = if closed { fill } else { Color32::TRANSPARENT };
+        let stroke_color = match &path_stroke.color {
+            ColorMode::Solid(color) => *color,
+            // Gradients are approximated with the color at the center of the bounding box.
+            ColorMode::UV(color) => color(bounds, bounds.center()),
+        };
+        let _ = writeln!(
+            self.body,
+            r#"<path d="{data}{close}"{}{} stroke-linejoin="round"/>"#,
+            paint("fill", fill),
+            stroke(Stroke::new(path_stroke.width, stroke_color)),
+        );
+    }
+
+    fn rect(&mut self, rect_shape: &RectShape) {
+        let rect = rect_shape.rect;
+        if !rect.is_positive() {
+            return;
+        }
+        if rect_shape.uv != Rect::ZERO {
+            self.textured_rect(
+                rect_shape.fill_texture_id,
+                rect,
+                rect_shape.uv,
+                rect_shape.fill,
+            );
+            if rect_shape.stroke.is_empty() {
+                return;
+            }
+            let _ = writeln!(
+                self.body,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none"{}/>"#,
+                num(rect.min.x),
+                num(rect.min.y),
+                num(rect.width()),
+                num(rect.height()),
+                stroke(rect_shape.stroke),
+            );
+            return;
+        }
+
+        let filter = match rect_shape.blur_width > 0.0 {
+            true => format!(
+                r#" filter="url(#blur{})""#,
+                self.blur(rect_shape.blur_width)
+            ),
+            false => String::new(),
+        };
+        let max_radius = rect.width().min(rect.height()) / 2.0;
+        let rounding = rect_shape.rounding;
+        let [nw, ne, sw, se] =
+            [rounding.nw, rounding.ne, rounding.sw, rounding.se].map(|r| r.clamp(0.0, max_radius));
+        let attributes = format!(
+            "{}{}{filter}",
+            paint("fill", rect_shape.fill),
+            stroke(rect_shape.stroke)
+        );
+        if nw == ne && nw == sw && nw == se {
+            let _ = writeln!(
+                self.body,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}"{attributes}/>"#,
+                num(rect.min.x),
+                num(rect.min.y),
+                num(rect.width()),
+                num(rect.height()),
+                num(nw),
+            );
+        } else {
+            let (min, max) = (rect.min, rect.max);
+            let _ = writeln!(
+                self.body,
+                "<path d=\"M{} {} H{} A{ne} {ne} 0 0 1 {} {} V{} A{se} {se} 0 0 1 {} {} H{} \
+                 A{sw} {sw} 0 0 1 {} {} V{} A{nw} {nw} 0 0 1 {} {} Z\"{attributes}/>",
+                num(min.x + nw),
+                num(min.y),
+                num(max.x - ne),
+                num(max.x),
+                num(min.y + ne),
+                num(max.y - se),
+                num(max.x - se),
+                num(max.y),
+                num(min.x + sw),
+                num(min.x),
+                num(max.y - sw),
+                num(min.y + nw),
+                num(min.x + nw),
+                num(min.y),
+                nw = num(nw),
+                ne = num(ne),
+                sw = num(sw),
+                se = num(se),
+            );
+        }
+    }
+
+    fn text(&mut self, text_shape: &TextShape) {
+        let TextShape {
+            pos,
+            galley,
+            underline,
+            override_text_color,
+            fallback_color,
+            opacity_factor,
+            angle,
+        } = text_shape;
+        if galley.is_empty() || *opacity_factor <= 0.0 {
+            return;
+        }
+        let Some((_, font_texture_size)) = self.texture(TextureId::default()) else {
+            return;
+        };
+        // Row meshes use texel UV coordinates.
+        let uv_normalizer = Vec2::new(
+            1.0 / font_texture_size[0] as f32,
+            1.0 / font_texture_size[1] as f32,
+        );
+
+        let rotate = match *angle == 0.0 {
+            true => String::new(),
+            false => format!(" rotate({})", num(angle.to_degrees())),
+        };
+        let _ = writeln!(
+            self.body,
+            r#"<g transform="translate({} {}){rotate}">"#,
+            num(pos.x),
+            num(pos.y),
+        );
+        for row in &galley.rows {
+            // The same colors as the ones the tessellator picks.
+            let vertices = row.visuals.mesh.vertices.iter().enumerate();
+            let mesh = Mesh {
+                indices: row.visuals.mesh.indices.clone(),
+                vertices: vertices
+                    .map(|(i, vertex)| {
+                        let mut color = vertex.color;
+                        if let Some(override_text_color) = override_text_color {
+                            if row.visuals.glyph_vertex_range.contains(&i) {
+                                color = *override_text_color;
+                            }
+                        } else if color == Color32::PLACEHOLDER {
+                            color = *fallback_color;
+                        }
+                        if *opacity_factor < 1.0 {
+                            color = color.gamma_multiply(*opacity_factor);
+                        }
+                        Vertex {
+                            pos: vertex.pos,
+                            uv: (vertex.uv.to_vec2() * uv_normalizer).to_pos2(),
+                            color,
+                        }
+                    })
+                    .collect(),
+                texture_id: TextureId::default(),
+            };
+            self.mesh(&mesh);
+
+            if *underline != Stroke::NONE {
+                let y = row.rect.bottom();
+                let _ = writeln!(
+                    self.body,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{}/>"#,
+                    num(row.rect.left()),
+                    num(y),
+                    num(row.rect.right()),
+                    num(y),
+                    stroke(*underline),
+                );
+            }
+        }
+        self.body.push_str("</g>\n");
+    }
+
+    /// Writes quads of the mesh as rects and images, and its other triangles as paths.
+    fn mesh(&mut self, mesh: &Mesh) {
+        let mut indices = mesh.indices.as_slice();
+        while indices.len() >= 3 {
+            if let Some((rect, uv, color)) = mesh_quad(mesh, indices) {
+                // Untextured parts of meshes sample a single texel.
+                if uv.width() * uv.height() > 0.0 {
+                    self.textured_rect(mesh.texture_id, rect, uv, color);
+                } else {
+                    self.fill_rect(rect, color);
+                }
+                indices = &indices[6..];
+                continue;
+            }
+
+            let triangle = [indices[0], indices[1], indices[2]].map(|i| mesh.vertices[i as usize]);
+            let [r, g, b, a] = triangle.iter().fold([0u32; 4], |sum, vertex| {
+                let color = vertex.color.to_array();
+                std::array::from_fn(|i| sum[i] + color[i] as u32)
+            });
+            let color = Color32::from_rgba_premultiplied(
+                (r / 3) as u8,
+                (g / 3) as u8,
+                (b / 3) as u8,
+                (a / 3) as u8,
+            );
+            let [p0, p1, p2] = triangle.map(|vertex| vertex.pos);
+            let _ = writeln!(
+                self.body,
+                r#"<path d="M{} {} L{} {} L{} {} Z"{}/>"#,
+                num(p0.x),
+                num(p0.y),
+                num(p1.x),
+                num(p1.y),
+                num(p2.x),
+                num(p2.y),
+                paint("fill", color),
+            );
+            indices = &indices[3..];
+        }
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color32) {
+        let _ = writeln!(
+            self.body,
+            r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+            num(rect.min.x),
+            num(rect.min.y),
+            num(rect.width()),
+            num(rect.height()),
+            paint("fill", color),
+        );
+    }
+
+    /// Writes the part of the texture at `uv` stretched over the rect.
+    fn textured_rect(&mut self, texture_id: TextureId, rect: Rect, uv: Rect, tint: Color32) {
+        let Some((id, [width, height])) = self.texture(texture_id) else {
+            self.fill_rect(rect, tint);
+            return;
+        };
+        let image = format!(
+            r##"<svg x="{}" y="{}" width="{}" height="{}" viewBox="{} {} {} {}" preserveAspectRatio="none"><use href="#texture{id}"/></svg>"##,
+            num(rect.min.x),
+            num(rect.min.y),
+            num(rect.width()),
+            num(rect.height()),
+            num(uv.min.x * width as f32),
+            num(uv.min.y * height as f32),
+            num(uv.width() * width as f32),
+            num(uv.height() * height as f32),
+        );
+        // Glyphs are white in the font texture, they are colored with a filter.
+        if texture_id == TextureId::default() {
+            let tint = self.tint(tint);
+            let _ = writeln!(self.body, r#"<g filter="url(#tint{tint})">{image}</g>"#);
+        } else if tint.a() < u8::MAX {
+            let opacity = num(tint.a() as f32 / 255.0);
+            let _ = writeln!(self.body, r#"<g opacity="{opacity}">{image}</g>"#);
+        } else {
+            let _ = writeln!(self.body, "{image}");
+        }
+    }
+
+    fn texture(&mut self, texture_id: TextureId) -> Option<(usize, [usize; 2])> {
+        if let Some(texture) = self.textures.get(&texture_id) {
+            return *texture;
+        }
+        let texture = (self.texture)(texture_id).and_then(|image| {
+            let data_uri = png_data_uri(&image)?;
+            let id = self.textures.len();
+            let [width, height] = image.size;
+            let _ = writeln!(
+                self.defs,
+                r#"<image id="texture{id}" width="{width}" height="{height}" href="{data_uri}"/>"#,
+            );
+            Some((id, image.size))
+        });
+        self.textures.insert(texture_id, texture);
+        texture
+    }
+
+    fn tint(&mut self, color: Color32) -> usize {
+        let tints = self.tints.len();
+        *self.tints.entry(color).or_insert_with(|| {
+            let [r, g, b, a] = color.to_srgba_unmultiplied();
+            let _ = writeln!(
+                self.defs,
+                r#"<filter id="tint{tints}"><feFlood flood-color="rgb({r},{g},{b})" flood-opacity="{}"/><feComposite operator="in" in2="SourceGraphic"/></filter>"#,
+                num(a as f32 / 255.0),
+            );
+            tints
+        })
+    }
+
+    fn blur(&mut self, blur_width: f32) -> usize {
+        let blurs = self.blurs.len();
+        *self.blurs.entry(blur_width.to_bits()).or_insert_with(|| {
+            // Egui blurs linearly over the width, a Gaussian blur of a third of it looks alike.
+            let _ = writeln!(
+                self.defs,
+                r#"<filter id="blur{blurs}" x="-50%" y="-50%" width="200%" height="200%"><feGaussianBlur stdDeviation="{}"/></filter>"#,
+                num(blur_width / 3.0),
+            );
+            blurs
+        })
+    }
+
+    fn clip_path(&mut self, clip_rect: Rect) -> usize {
+        let id = self.clip_paths;
+        self.clip_paths += 1;
+        let _ = writeln!(
+            self.defs,
+            r#"<clipPath id="clip{id}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+            num(clip_rect.min.x),
+            num(clip_rect.min.y),
+            num(clip_rect.width().max(0.0)),
+            num(clip_rect.height().max(0.0)),
+        );
+        id
+    }
+}
+
+/// Returns the rect, UV rect and color of the next two triangles if they form an axis aligned
+/// quad of a single color, as rects, images and glyphs are tessellated.
+fn mesh_quad(mesh: &Mesh, indices: &[u32]) -> Option<(Rect, Rect, Color32)> {
+    let vertices: Vec<Vertex> = indices
+        .get(..6)?
+        .iter()
+        .map(|&i| mesh.vertices.get(i as usize).copied())
+        .collect::<Option<_>>()?;
+    let rect = Rect::from_points(&vertices.iter().map(|vertex| vertex.pos).collect::<Vec<_>>());
+    let color = vertices[0].color;
+    let mut corner_uvs = [None; 4];
+    for vertex in &vertices {
+        let (right, bottom) = (vertex.pos.x == rect.max.x, vertex.pos.y == rect.max.y);
+        let is_corner =
+            (right || vertex.pos.x == rect.min.x) && (bottom || vertex.pos.y == rect.min.y);
+        if vertex.color != color || !is_corner {
+            return None;
+        }
+        corner_uvs[right as usize + 2 * bottom as usize] = Some(vertex.uv);
+    }
+    // The triangles have to share a diagonal to cover the quad.
+    let shared: Vec<Pos2> = vertices[..3]
+        .iter()
+        .filter(|vertex| vertices[3..].iter().any(|other| other.pos == vertex.pos))
+        .map(|vertex| vertex.pos)
+        .collect();
+    let shares_diagonal =
+        shared.len() == 2 && shared[0].x != shared[1].x && shared[0].y != shared[1].y;
+    let [Some(uv_min), Some(uv_ne), Some(uv_sw), Some(uv_max)] = corner_uvs else {
+        return None;
+    };
+    // The texture has to be mapped without rotation or skew.
+    let is_aligned =
+        uv_ne == Pos2::new(uv_max.x, uv_min.y) && uv_sw == Pos2::new(uv_min.x, uv_max.y);
+    (shares_diagonal && is_aligned && rect.is_positive())
+        .then(|| (rect, Rect::from_min_max(uv_min, uv_max), color))
+}
+
+fn paint(attribute: &str, color: Color32) -> String {
+    if color.a() == 0 {
+        return format!(r#" {attribute}="none""#);
+    }
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    match a {
+        u8::MAX => format!(r#" {attribute}="rgb({r},{g},{b})""#),
+        _ => format!(
+            r#" {attribute}="rgb({r},{g},{b})" {attribute}-opacity="{}""#,
+            num(a as f32 / 255.0)
+        ),
+    }
+}
+
+fn stroke(stroke: Stroke) -> String {
+    if stroke.is_empty() {
+        return String::new();
+    }
+    format!(
+        r#"{} stroke-width="{}""#,
+        paint("stroke", stroke.color),
+        num(stroke.width)
+    )
+}
+
+/// Rounds coordinates, to keep the document readable.
+fn num(value: f32) -> f32 {
+    // Adding zero turns `-0` into `0`.
+    (value * 1000.0).round() / 1000.0 + 0.0
+}
+
+fn png_data_uri(image: &ColorImage) -> Option<String> {
+    use ::image::ImageEncoder;
+
+    let rgba: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|color| color.to_srgba_unmultiplied())
+        .collect();
+    let mut png = Vec::new();
+    ::image::codecs::png::PngEncoder::new(&mut png)
+        .write_image(
+            &rgba,
+            image.width() as u32,
+            image.height() as u32,
+            ::image::ExtendedColorType::Rgba8,
+        )
+        .ok()?;
+    Some(format!("data:image/png;base64,{}", base64(&png)))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{FontId, Rounding};
+
+    fn clipped(clip_rect: Rect, shape: impl Into<Shape>) -> ClippedShape {
+        ClippedShape {
+            clip_rect,
+            shape: shape.into(),
+        }
+    }
+
+    #[test]
+    fn test_base64() {
+        // Test vectors of RFC 4648.
+        for (input, expected) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64(input.as_bytes()), expected);
+        }
+        assert_eq!(base64(&[0xFF, 0xFE, 0xFD]), "//79");
+    }
+
+    #[test]
+    fn test_num() {
+        assert_eq!(num(1.23456), 1.235);
+        assert_eq!(num(-0.0001).to_string(), "0");
+    }
+
+    #[test]
+    fn test_rect() {
+        let screen_rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(100.0, 50.0));
+        let rect = Shape::rect_filled(
+            Rect::from_min_size(Pos2::new(10.0, 20.0), Vec2::new(30.0, 15.5)),
+            Rounding::same(2.0),
+            Color32::from_rgb(255, 0, 0),
+        );
+        let svg = shapes_to_svg(&[clipped(screen_rect, rect)], screen_rect.size(), |_| None);
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50" viewBox="0 0 100 50">"#
+        ));
+        assert!(svg.contains(
+            r#"<rect x="10" y="20" width="30" height="15.5" rx="2" fill="rgb(255,0,0)"/>"#
+        ));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_clipped_shapes() {
+        let size = Vec2::new(100.0, 100.0);
+        let clip_rect = Rect::from_min_max(Pos2::new(10.0, 10.0), Pos2::new(200.0, 50.0));
+        let circle = Shape::circle_filled(Pos2::new(20.0, 20.0), 5.0, Color32::WHITE);
+        let shapes = [
+            clipped(clip_rect, circle.clone()),
+            clipped(clip_rect, circle.clone()),
+            clipped(Rect::EVERYTHING, circle),
+        ];
+        let svg = shapes_to_svg(&shapes, size, |_| None);
+        // Clip rects are cut to the document, consecutive shapes with the same one share a group.
+        assert!(svg.contains(
+            r#"<clipPath id="clip0"><rect x="10" y="10" width="90" height="40"/></clipPath>"#
+        ));
+        assert!(svg.contains(
+            r#"<clipPath id="clip1"><rect x="0" y="0" width="100" height="100"/></clipPath>"#
+        ));
+        assert_eq!(svg.matches("<g clip-path=").count(), 2);
+        assert_eq!(svg.matches("<circle ").count(), 3);
+        assert_eq!(svg.matches("<g ").count(), svg.matches("</g>").count());
+    }
+
+    #[test]
+    fn test_image() {
+        let size = Vec2::new(64.0, 64.0);
+        let texture_id = TextureId::User(7);
+        let rect = Rect::from_min_size(Pos2::new(8.0, 8.0), Vec2::new(32.0, 16.0));
+        let uv = Rect::from_min_max(Pos2::new(0.5, 0.0), Pos2::new(1.0, 1.0));
+        let image = Shape::image(texture_id, rect, uv, Color32::WHITE);
+        let mut requested = Vec::new();
+        let svg = shapes_to_svg(&[clipped(Rect::EVERYTHING, image)], size, |id| {
+            requested.push(id);
+            Some(ColorImage::new([4, 2], Color32::BLUE))
+        });
+        assert_eq!(requested, vec![texture_id]);
+        assert!(svg
+            .contains(r#"<image id="texture0" width="4" height="2" href="data:image/png;base64,"#));
+        assert!(svg.contains(
+            r##"<svg x="8" y="8" width="32" height="16" viewBox="2 0 2 2" preserveAspectRatio="none"><use href="#texture0"/></svg>"##
+        ));
+    }
+
+    #[test]
+    fn test_missing_image_is_filled_with_tint() {
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(10.0));
+        let image = Shape::image(
+            TextureId::User(0),
+            rect,
+            Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+            Color32::GREEN,
+        );
+        let svg = shapes_to_svg(&[clipped(rect, image)], rect.size(), |_| None);
+        assert!(svg.contains(r#"<rect x="0" y="0" width="10" height="10" fill="rgb(0,255,0)"/>"#));
+    }
+
+    #[test]
+    fn test_text() {
+        let ctx = egui::Context::default();
+        ctx.begin_pass(egui::RawInput::default());
+        let (galley, font_image_size) = ctx.fonts(|fonts| {
+            let galley = fonts.layout_no_wrap("Hi".to_owned(), FontId::default(), Color32::WHITE);
+            (galley, fonts.font_image_size())
+        });
+        let text = Shape::galley(Pos2::new(5.0, 6.0), galley, Color32::WHITE);
+        let svg = shapes_to_svg(
+            &[clipped(Rect::EVERYTHING, text)],
+            Vec2::new(100.0, 100.0),
+            |id| {
+                assert_eq!(id, TextureId::default());
+                Some(ColorImage::new(font_image_size, Color32::WHITE))
+            },
+        );
+        assert!(svg.contains(r#"<g transform="translate(5 6)">"#));
+        // Both glyphs are drawn from the font texture, with the same tint.
+        assert_eq!(svg.matches(r##"<use href="#texture0"/>"##).count(), 2);
+        assert_eq!(svg.matches("<filter id=\"tint").count(), 1);
+        assert!(svg.contains(r#"<feFlood flood-color="rgb(255,255,255)" flood-opacity="1"/>"#));
+    }
+
+    #[test]
+    fn test_mesh_quad() {
+        let mut mesh = Mesh::with_texture(TextureId::User(1));
+        let rect = Rect::from_min_size(Pos2::new(1.0, 2.0), Vec2::new(3.0, 4.0));
+        let uv = Rect::from_min_max(Pos2::new(0.0, 0.5), Pos2::new(0.5, 1.0));
+        mesh.add_rect_with_uv(rect, uv, Color32::RED);
+        assert_eq!(
+            mesh_quad(&mesh, &mesh.indices),
+            Some((rect, uv, Color32::RED))
+        );
+
+        // A triangle fan that isn't a quad.
+        mesh.vertices[2].pos = Pos2::new(2.0, 9.0);
+        assert_eq!(mesh_quad(&mesh, &mesh.indices), None);
+    }
+}
@@ -3,8 +3,8 @@ use crate::text_agent::{is_mobile_safari, update_text_agent};
 #[cfg(feature = "render")]
 use crate::EguiRenderToTextureHandle;
 use crate::{
-    EguiContext, EguiContextQuery, EguiContextQueryItem, EguiFullOutput, EguiInput, EguiSettings,
-    RenderTargetSize,
+    EguiContext, EguiContextQuery, EguiContextQueryItem, EguiError, EguiFullOutput, EguiInput,
+    EguiSettings, RenderTargetSize,
 };
 use bevy_ecs::{
     event::EventWriter,
@@ -26,7 +26,9 @@ use bevy_winit::{EventLoopProxy, WakeUp};
 #[cfg(feature = "render")]
 use bevy_asset::Assets;
 #[cfg(feature = "render")]
-use bevy_render::texture::Image;
+use bevy_render::{camera::RenderTarget, texture::Image};
+#[cfg(feature = "render")]
+use bevy_window::{PrimaryWindow, WindowRef};
 use std::{marker::PhantomData, time::Duration};
 
 #[allow(missing_docs)]
@@ -84,16 +86,34 @@ pub struct InputResources<'w, 's> {
 pub struct ContextSystemParams<'w, 's> {
     pub contexts: Query<'w, 's, EguiContextQuery>,
     pub is_macos: Local<'s, bool>,
+    pub egui_errors: EventWriter<'w, EguiError>,
     #[system_param(ignore)]
     _marker: PhantomData<&'s ()>,
 }
 
 impl ContextSystemParams<'_, '_> {
-    fn window_context(&mut self, window: Entity) -> Option<EguiContextQueryItem> {
+    /// Returns contexts of the cameras that render to the window.
+    #[cfg(feature = "render")]
+    fn camera_contexts(
+        &mut self,
+        window: Entity,
+    ) -> impl Iterator<Item = EguiContextQueryItem<'_>> {
+        self.contexts.iter_mut().filter(move |context| {
+            context
+                .camera_context
+                .as_ref()
+                .is_some_and(|camera_context| camera_context.window == Some(window))
+        })
+    }
+
+    fn window_context(&mut self, window: Entity) -> Option<EguiContextQueryItem<'_>> {
         match self.contexts.get_mut(window) {
             Ok(context) => Some(context),
-            Err(err @ QueryEntityError::AliasedMutability(_)) => {
-                panic!("Failed to get an Egui context for a window ({window:?}): {err:?}");
+            Err(QueryEntityError::AliasedMutability(_)) => {
+                let error = EguiError::AliasedContextAccess(window);
+                bevy_log::error!("Failed to get an Egui context for a window: {error}");
+                self.egui_errors.send(error);
+                None
             }
             Err(
                 err @ QueryEntityError::NoSuchEntity(_)
@@ -184,6 +204,28 @@ pub fn process_input_system(
     };
 
     for event in input_events.ev_cursor.read() {
+        #[cfg(feature = "render")]
+        for mut context in context_params.camera_contexts(event.window) {
+            let Some(camera_context) = context.camera_context.as_deref_mut() else {
+                continue;
+            };
+            if camera_context.viewport.contains(event.position) {
+                let scale_factor = context.egui_settings.scale_factor;
+                let (x, y): (f32, f32) =
+                    ((event.position - camera_context.viewport.min) / scale_factor).into();
+                let mouse_position = egui::pos2(x, y);
+                context.ctx.mouse_position = mouse_position;
+                context
+                    .egui_input
+                    .events
+                    .push(egui::Event::PointerMoved(mouse_position));
+                camera_context.hovered = true;
+            } else if camera_context.hovered {
+                context.egui_input.events.push(egui::Event::PointerGone);
+                camera_context.hovered = false;
+            }
+        }
+
         let Some(mut window_context) = context_params.window_context(event.window) else {
             continue;
         };
@@ -199,12 +241,6 @@ pub fn process_input_system(
     }
 
     for event in input_events.ev_mouse_button_input.read() {
-        let Some(mut window_context) = context_params.window_context(event.window) else {
-            continue;
-        };
-        #[cfg(feature = "log_input_events")]
-        bevy_log::info!("{event:?}");
-
         let button = match event.button {
             MouseButton::Left => Some(egui::PointerButton::Primary),
             MouseButton::Right => Some(egui::PointerButton::Secondary),
@@ -215,6 +251,31 @@ pub fn process_input_system(
             ButtonState::Pressed => true,
             ButtonState::Released => false,
         };
+
+        // Releases are sent to all the camera contexts, so that buttons don't get stuck
+        // if the pointer leaves a viewport while dragging.
+        #[cfg(feature = "render")]
+        if let Some(button) = button {
+            for mut context in context_params.camera_contexts(event.window) {
+                if pressed && !context.camera_context.as_ref().is_some_and(|c| c.hovered) {
+                    continue;
+                }
+                let pos = context.ctx.mouse_position;
+                context.egui_input.events.push(egui::Event::PointerButton {
+                    pos,
+                    button,
+                    pressed,
+                    modifiers,
+                });
+            }
+        }
+
+        let Some(mut window_context) = context_params.window_context(event.window) else {
+            continue;
+        };
+        #[cfg(feature = "log_input_events")]
+        bevy_log::info!("{event:?}");
+
         if let Some(button) = button {
             window_context
                 .egui_input
@@ -229,12 +290,6 @@ pub fn process_input_system(
     }
 
     for event in input_events.ev_mouse_wheel.read() {
-        let Some(mut window_context) = context_params.window_context(event.window) else {
-            continue;
-        };
-        #[cfg(feature = "log_input_events")]
-        bevy_log::info!("{event:?}");
-
         let delta = egui::vec2(event.x, event.y);
 
         let unit = match event.unit {
@@ -242,6 +297,23 @@ pub fn process_input_system(
             MouseScrollUnit::Pixel => egui::MouseWheelUnit::Point,
         };
 
+        #[cfg(feature = "render")]
+        for mut context in context_params.camera_contexts(event.window) {
+            if context.camera_context.as_ref().is_some_and(|c| c.hovered) {
+                context.egui_input.events.push(egui::Event::MouseWheel {
+                    unit,
+                    delta,
+                    modifiers,
+                });
+            }
+        }
+
+        let Some(mut window_context) = context_params.window_context(event.window) else {
+            continue;
+        };
+        #[cfg(feature = "log_input_events")]
+        bevy_log::info!("{event:?}");
+
         window_context
             .egui_input
             .events
@@ -314,58 +386,69 @@ pub fn process_input_system(
         #[cfg(feature = "log_input_events")]
         bevy_log::info!("{event:?}");
 
+        let mut egui_events = Vec::new();
+
         if text_event_allowed && event.state.is_pressed() {
             match &event.logical_key {
                 Key::Character(char) if char.matches(char::is_control).count() == 0 => {
-                    (window_context.egui_input.events).push(egui::Event::Text(char.to_string()));
+                    egui_events.push(egui::Event::Text(char.to_string()));
                 }
                 Key::Space => {
-                    (window_context.egui_input.events).push(egui::Event::Text(" ".into()));
+                    egui_events.push(egui::Event::Text(" ".into()));
                 }
                 _ => (),
             }
         }
 
-        let (Some(key), physical_key) = (
+        if let (Some(key), physical_key) = (
             bevy_to_egui_key(&event.logical_key),
             bevy_to_egui_physical_key(&event.key_code),
-        ) else {
-            continue;
-        };
-
-        let egui_event = egui::Event::Key {
-            key,
-            pressed: event.state.is_pressed(),
-            repeat: false,
-            modifiers,
-            physical_key,
-        };
-        window_context.egui_input.events.push(egui_event);
+        ) {
+            egui_events.push(egui::Event::Key {
+                key,
+                pressed: event.state.is_pressed(),
+                repeat: false,
+                modifiers,
+                physical_key,
+            });
 
-        // We also check that it's an `ButtonState::Pressed` event, as we don't want to
-        // copy, cut or paste on the key release.
-        #[cfg(all(
-            feature = "manage_clipboard",
-            not(target_os = "android"),
-            not(target_arch = "wasm32")
-        ))]
-        if command && event.state.is_pressed() {
-            match key {
-                egui::Key::C => {
-                    window_context.egui_input.events.push(egui::Event::Copy);
-                }
-                egui::Key::X => {
-                    window_context.egui_input.events.push(egui::Event::Cut);
-                }
-                egui::Key::V => {
-                    if let Some(contents) = input_resources.egui_clipboard.get_contents() {
-                        window_context
-                            .egui_input
-                            .events
-                            .push(egui::Event::Text(contents))
+            // We also check that it's an `ButtonState::Pressed` event, as we don't want to
+            // copy, cut or paste on the key release.
+            #[cfg(all(
+                feature = "manage_clipboard",
+                not(target_os = "android"),
+                not(target_arch = "wasm32")
+            ))]
+            if command && event.state.is_pressed() {
+                match key {
+                    egui::Key::C => {
+                        egui_events.push(egui::Event::Copy);
+                    }
+                    egui::Key::X => {
+                        egui_events.push(egui::Event::Cut);
                     }
+                    egui::Key::V => {
+                        if let Some(contents) = input_resources.egui_clipboard.get_contents() {
+                            egui_events.push(egui::Event::Text(contents))
+                        }
+                    }
+                    _ => {}
                 }
-                _ => {}
+            }
+        }
+
+        window_context
+            .egui_input
+            .events
+            .extend(egui_events.iter().cloned());
+
+        #[cfg(feature = "render")]
+        for mut context in context_params.camera_contexts(event.window) {
+            if context.ctx.get_mut().wants_keyboard_input() {
+                context
+                    .egui_input
+                    .events
+                    .extend(egui_events.iter().cloned());
             }
         }
     }
@@ -516,7 +599,15 @@ pub fn process_input_system(
 pub fn update_contexts_system(
     mut context_params: ContextSystemParams,
     #[cfg(feature = "render")] images: Res<Assets<Image>>,
+    #[cfg(feature = "render")] primary_window: Query<Entity, With<PrimaryWindow>>,
+    #[cfg(feature = "render")] mut loading_images: Local<bevy_utils::HashSet<Entity>>,
+    #[cfg(feature = "render")] mut removed_contexts: RemovedComponents<EguiContext>,
 ) {
+    #[cfg(feature = "render")]
+    for entity in removed_contexts.read() {
+        loading_images.remove(&entity);
+    }
+
     for mut context in context_params.contexts.iter_mut() {
         let mut render_target_size = None;
         if let Some(window) = context.window {
@@ -528,17 +619,70 @@ pub fn update_contexts_system(
         }
         #[cfg(feature = "render")]
         if let Some(EguiRenderToTextureHandle(handle)) = context.render_to_texture.as_deref() {
-            let image = images.get(handle).expect("rtt handle should be valid");
+            let Some(image) = images.get(handle) else {
+                // It's expected while the image is loading, so we don't log it as an error,
+                // and we report it only once until the image becomes available.
+                if loading_images.insert(context.render_target) {
+                    let error = EguiError::RenderToTextureImageNotLoaded(context.render_target);
+                    bevy_log::debug!("Skipping the Egui context: {error}");
+                    context_params.egui_errors.send(error);
+                }
+                continue;
+            };
+            loading_images.remove(&context.render_target);
             let size = image.size_f32();
             render_target_size = Some(RenderTargetSize {
                 physical_width: size.x,
                 physical_height: size.y,
-                scale_factor: 1.0,
+                scale_factor: context
+                    .render_to_texture_size
+                    .map_or(1.0, |render_to_texture_size| {
+                        render_to_texture_size.scale_factor
+                    }),
             })
         }
+        #[cfg(feature = "render")]
+        if let (Some(camera), Some(camera_context)) =
+            (context.camera, context.camera_context.as_deref_mut())
+        {
+            // Camera viewports get computed once the camera renders its first frame.
+            let (Some(viewport), Some(scale_factor)) = (
+                camera.physical_viewport_rect(),
+                camera.target_scaling_factor(),
+            ) else {
+                continue;
+            };
+            camera_context.window = match &camera.target {
+                RenderTarget::Window(WindowRef::Primary) => primary_window.get_single().ok(),
+                RenderTarget::Window(WindowRef::Entity(window)) => Some(*window),
+                RenderTarget::Image(_) | RenderTarget::TextureView(_) => None,
+            };
+            camera_context.viewport = bevy_math::Rect::from_corners(
+                viewport.min.as_vec2() / scale_factor,
+                viewport.max.as_vec2() / scale_factor,
+            );
+            render_target_size = Some(RenderTargetSize::new(
+                viewport.width() as f32,
+                viewport.height() as f32,
+                scale_factor,
+            ));
+        }
+        #[cfg(feature = "render")]
+        if let Some(atlas_region) = context.atlas_region {
+            let size = atlas_region.physical_size().as_vec2();
+            render_target_size = Some(RenderTargetSize::new(
+                size.x,
+                size.y,
+                atlas_region.scale_factor,
+            ));
+        }
+        #[cfg(feature = "world_space")]
+        if let Some(world_space) = context.world_space {
+            render_target_size = Some(world_space.render_target_size());
+        }
 
         let Some(new_render_target_size) = render_target_size else {
-            error!("bevy_egui context without window or render to texture!");
+            error!("bevy_egui context without window, render to texture, camera or world space!");
             continue;
         };
         let width = new_render_target_size.physical_width
@@ -557,11 +701,30 @@ pub fn update_contexts_system(
             egui::pos2(width, height),
         ));
 
+        // Contexts rendered at a reduced resolution keep their logical size, so that input
+        // and the transform aren't affected, and get fewer pixels per point.
+        #[allow(unused_mut)]
+        let mut render_scale = context.egui_settings.clamped_render_scale();
+        // Atlas regions are drawn straight into the atlas, there's no intermediate texture to
+        // upscale them.
+        #[cfg(feature = "render")]
+        if context.atlas_region.is_some() {
+            render_scale = 1.0;
+        }
+        let new_render_target_size = RenderTargetSize::new(
+            new_render_target_size.physical_width * render_scale,
+            new_render_target_size.physical_height * render_scale,
+            new_render_target_size.scale_factor * render_scale,
+        );
+
         context.ctx.get_mut().set_pixels_per_point(
             new_render_target_size.scale_factor * context.egui_settings.scale_factor,
         );
 
-        *context.render_target_size = new_render_target_size;
+        // Reactive rendering relies on the change detection to repaint resized contexts.
+        context
+            .render_target_size
+            .set_if_neq(new_render_target_size);
     }
 }
 
@@ -585,6 +748,31 @@ pub fn end_pass_system(
     }
 }
 
+/// Decides whether a context is tessellated again, returns the decision and the time (since
+/// startup) of the next repaint.
+///
+/// Without reactive rendering, contexts are repainted every frame. Otherwise, they're repainted
+/// when Egui asks for it right away, when a delayed repaint is due, or when something Egui
+/// doesn't know about (`inputs_changed`) affects the output.
+fn repaint_decision(
+    reactive_rendering: bool,
+    next_repaint: Option<Duration>,
+    repaint_delay: Duration,
+    now: Duration,
+    inputs_changed: bool,
+) -> (bool, Option<Duration>) {
+    let deadline = now.saturating_add(repaint_delay);
+    let repaint = !reactive_rendering
+        || repaint_delay.is_zero()
+        || next_repaint.map_or(true, |next_repaint| next_repaint <= now)
+        || inputs_changed;
+    match repaint {
+        true => (true, Some(deadline)),
+        // Egui might still want to repaint later, even if the current pass didn't change anything.
+        false => (false, next_repaint.min(Some(deadline))),
+    }
+}
+
 /// Reads Egui output.
 pub fn process_output_system(
     mut contexts: Query<EguiContextQuery>,
@@ -593,11 +781,16 @@ pub fn process_output_system(
     mut event: EventWriter<RequestRedraw>,
     #[cfg(windows)] mut last_cursor_icon: Local<bevy_utils::HashMap<Entity, egui::CursorIcon>>,
     event_loop_proxy: Option<NonSend<EventLoopProxy<WakeUp>>>,
+    mut scheduled_wake_up: Local<Option<Duration>>,
+    mut wake_up_scheduler: Local<WakeUpScheduler>,
+    time: Res<Time<Real>>,
 ) {
     let mut should_request_redraw = false;
+    let mut wake_up_after = Duration::MAX;
+    let now = time.elapsed();
 
     for mut context in contexts.iter_mut() {
-        let ctx = context.ctx.get_mut();
+        let ctx = context.ctx.get_mut().clone();
         let Some(full_output) = context.egui_full_output.0.take() else {
             bevy_log::error!("bevy_egui pass output has not been prepared (if EguiSettings::run_manually is set to true, make sure to call egui::Context::run or egui::Context::begin_pass and egui::Context::end_pass)");
             continue;
@@ -607,12 +800,41 @@ pub fn process_output_system(
             shapes,
             textures_delta,
             pixels_per_point,
-            viewport_output: _,
+            viewport_output,
         } = full_output;
-        let paint_jobs = ctx.tessellate(shapes, pixels_per_point);
+        let repaint_delay = viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .map_or(Duration::MAX, |viewport_output| {
+                viewport_output.repaint_delay
+            });
+        wake_up_after = wake_up_after.min(repaint_delay);
 
-        context.render_output.paint_jobs = paint_jobs;
-        context.render_output.textures_delta.append(textures_delta);
+        let inputs_changed = !textures_delta.is_empty()
+            || context.render_target_size.is_changed()
+            || context.egui_settings.is_changed();
+        // Changing the order recreates the render node, which has to be filled again.
+        #[cfg(feature = "render")]
+        let inputs_changed = inputs_changed
+            || context
+                .render_order
+                .as_ref()
+                .is_some_and(|render_order| render_order.is_changed());
+
+        let (repaint, next_repaint) = repaint_decision(
+            context.egui_settings.reactive_rendering,
+            context.ctx.next_repaint,
+            repaint_delay,
+            now,
+            inputs_changed,
+        );
+        context.ctx.next_repaint = next_repaint;
+        if repaint {
+            let paint_jobs = ctx.tessellate(shapes, pixels_per_point);
+            context.render_output.paint_jobs = paint_jobs;
+        }
+        if !textures_delta.is_empty() {
+            context.render_output.textures_delta.append(textures_delta);
+        }
 
         context.egui_output.platform_output = platform_output.clone();
 
@@ -643,22 +865,11 @@ pub fn process_output_system(
             set_icon();
         }
 
-        let needs_repaint = !context.render_output.is_empty();
-        should_request_redraw |= ctx.has_requested_repaint() && needs_repaint;
-
-        // The resource doesn't exist in the headless mode.
-        if let Some(event_loop_proxy) = &event_loop_proxy {
-            // A zero duration indicates that it's an outstanding redraw request, which gives Egui an
-            // opportunity to settle the effects of interactions with widgets. Such repaint requests
-            // are processed not immediately but on a next frame. In this case, we need to indicate to
-            // winit, that it needs to wake up next frame as well even if there are no inputs.
-            //
-            // TLDR: this solves repaint corner cases of `WinitSettings::desktop_app()`.
-            if let Some(Duration::ZERO) =
-                ctx.viewport(|viewport| viewport.input.wants_repaint_after())
-            {
-                let _ = event_loop_proxy.send_event(WakeUp);
-            }
+        if context.egui_settings.reactive_rendering {
+            should_request_redraw |= repaint_delay.is_zero();
+        } else {
+            let needs_repaint = !context.render_output.is_empty();
+            should_request_redraw |= ctx.has_requested_repaint() && needs_repaint;
         }
 
         #[cfg(feature = "open_url")]
@@ -685,6 +896,114 @@ pub fn process_output_system(
     if should_request_redraw {
         event.send(RequestRedraw);
     }
+
+    // The resource doesn't exist in the headless mode.
+    if let Some(event_loop_proxy) = &event_loop_proxy {
+        if wake_up_after.is_zero() {
+            // A zero duration indicates that it's an outstanding redraw request, which gives Egui an
+            // opportunity to settle the effects of interactions with widgets. Such repaint requests
+            // are processed not immediately but on a next frame. In this case, we need to indicate to
+            // winit, that it needs to wake up next frame as well even if there are no inputs.
+            //
+            // TLDR: this solves repaint corner cases of `WinitSettings::desktop_app()`.
+            let _ = event_loop_proxy.send_event(WakeUp);
+        } else if wake_up_after < Duration::MAX {
+            // Delayed repaints (blinking text cursors, tooltips, etc.) need to wake up winit as well.
+            // We avoid scheduling a wake-up if there's already an earlier one pending.
+            let deadline = now.saturating_add(wake_up_after);
+            if scheduled_wake_up.map_or(true, |scheduled| scheduled <= now || deadline < scheduled)
+            {
+                wake_up_scheduler.schedule(event_loop_proxy, wake_up_after);
+                *scheduled_wake_up = Some(deadline);
+            }
+        }
+    }
+}
+
+/// Sends [`WakeUp`] to the event loop for delayed repaints.
+///
+/// Natively, a single long-lived thread sleeps until the earliest scheduled wake-up, scheduling
+/// an earlier one replaces it. The thread exits when the scheduler is dropped.
+#[derive(Default)]
+pub struct WakeUpScheduler {
+    #[cfg(not(target_arch = "wasm32"))]
+    sender: Option<std::sync::mpsc::Sender<std::time::Instant>>,
+}
+
+impl WakeUpScheduler {
+    /// Schedules a wake-up after the specified delay.
+    pub fn schedule(&mut self, event_loop_proxy: &EventLoopProxy<WakeUp>, delay: Duration) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Some(deadline) = std::time::Instant::now().checked_add(delay) else {
+                return;
+            };
+            let sender = self.sender.get_or_insert_with(|| {
+                let (sender, receiver) = std::sync::mpsc::channel();
+                let event_loop_proxy = event_loop_proxy.clone();
+                std::thread::Builder::new()
+                    .name("egui wake-up".to_string())
+                    .spawn(move || {
+                        run_wake_up_timer(&receiver, || {
+                            let _ = event_loop_proxy.send_event(WakeUp);
+                        });
+                    })
+                    .expect("failed to spawn the Egui wake-up thread");
+                sender
+            });
+            let _ = sender.send(deadline);
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::JsCast;
+
+            let Some(window) = web_sys::window() else {
+                return;
+            };
+            let event_loop_proxy = event_loop_proxy.clone();
+            let callback = wasm_bindgen::closure::Closure::once_into_js(move || {
+                let _ = event_loop_proxy.send_event(WakeUp);
+            });
+            if let Err(err) = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                callback.unchecked_ref(),
+                delay.as_millis().min(i32::MAX as u128) as i32,
+            ) {
+                bevy_log::error!(
+                    "Failed to schedule a wake-up: {}",
+                    crate::string_from_js_value(&err)
+                );
+            }
+        }
+    }
+}
+
+/// Waits for the earliest of the received deadlines and calls `wake_up`, until the sender gets
+/// dropped.
+#[cfg(not(target_arch = "wasm32"))]
+fn run_wake_up_timer(receiver: &std::sync::mpsc::Receiver<std::time::Instant>, wake_up: impl Fn()) {
+    use std::sync::mpsc::RecvTimeoutError;
+
+    let mut deadline: Option<std::time::Instant> = None;
+    loop {
+        let received = match deadline {
+            Some(deadline) => {
+                receiver.recv_timeout(deadline.saturating_duration_since(std::time::Instant::now()))
+            }
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(new_deadline) => {
+                deadline =
+                    Some(deadline.map_or(new_deadline, |deadline| deadline.min(new_deadline)));
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                deadline = None;
+                wake_up();
+            }
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
 }
 
 fn egui_to_winit_cursor_icon(cursor_icon: egui::CursorIcon) -> Option<bevy_window::CursorIcon> {
@@ -875,3 +1194,86 @@ pub fn bevy_to_egui_physical_key(key: &KeyCode) -> Option<egui::Key> {
     };
     Some(key)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn test_wake_up_timer_wakes_up_at_earliest_deadline() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let (wake_up_sender, wake_ups) = std::sync::mpsc::channel();
+        let timer = std::thread::spawn(move || {
+            run_wake_up_timer(&receiver, || wake_up_sender.send(Instant::now()).unwrap());
+        });
+
+        let start = Instant::now();
+        sender.send(start + Duration::from_secs(60)).unwrap();
+        // An earlier deadline replaces the pending one.
+        sender.send(start + Duration::from_millis(20)).unwrap();
+        let woken_up_at = wake_ups.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(woken_up_at >= start + Duration::from_millis(20));
+
+        // The thread exits once the sender is dropped, the later deadline is abandoned.
+        drop(sender);
+        timer.join().unwrap();
+        assert!(wake_ups.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_repaint_decision_without_reactive_rendering() {
+        let (repaint, next_repaint) =
+            repaint_decision(false, Some(10 * SECOND), Duration::MAX, SECOND, false);
+        assert!(repaint);
+        assert_eq!(next_repaint, Some(Duration::MAX));
+    }
+
+    #[test]
+    fn test_repaint_decision_first_pass() {
+        assert_eq!(
+            repaint_decision(true, None, Duration::MAX, SECOND, false),
+            (true, Some(Duration::MAX))
+        );
+    }
+
+    #[test]
+    fn test_repaint_decision_immediate_repaint() {
+        assert_eq!(
+            repaint_decision(true, Some(10 * SECOND), Duration::ZERO, SECOND, false),
+            (true, Some(SECOND))
+        );
+    }
+
+    #[test]
+    fn test_repaint_decision_idle() {
+        // Nothing changed and no repaint is due: the earlier deadline is kept.
+        assert_eq!(
+            repaint_decision(true, Some(5 * SECOND), 10 * SECOND, SECOND, false),
+            (false, Some(5 * SECOND))
+        );
+        // A repaint requested for earlier than the pending one brings it forward.
+        assert_eq!(
+            repaint_decision(true, Some(5 * SECOND), SECOND, SECOND, false),
+            (false, Some(2 * SECOND))
+        );
+    }
+
+    #[test]
+    fn test_repaint_decision_due() {
+        assert_eq!(
+            repaint_decision(true, Some(SECOND), Duration::MAX, SECOND, false),
+            (true, Some(Duration::MAX))
+        );
+    }
+
+    #[test]
+    fn test_repaint_decision_inputs_changed() {
+        assert_eq!(
+            repaint_decision(true, Some(5 * SECOND), 2 * SECOND, SECOND, true),
+            (true, Some(3 * SECOND))
+        );
+    }
+}
@@ -0,0 +1,356 @@
+use crate::{
+    EguiContext, EguiFullOutput, EguiInput, EguiManagedTextures, EguiOutput, EguiRenderOutput,
+    EguiSettings, EguiUserTextures, RenderTargetSize,
+};
+use bevy_asset::{Asset, Assets, Handle};
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::{BuildChildren, DespawnRecursiveExt};
+use bevy_math::{Vec2, Vec3};
+use bevy_pbr::{Material, MaterialMeshBundle, MaterialPipeline, MaterialPipelineKey};
+use bevy_reflect::TypePath;
+use bevy_render::{
+    alpha::AlphaMode,
+    mesh::{Indices, Mesh, MeshVertexAttribute, MeshVertexBufferLayoutRef, PrimitiveTopology},
+    primitives::Aabb,
+    render_asset::RenderAssetUsages,
+    render_resource::{
+        AsBindGroup, RenderPipelineDescriptor, Shader, ShaderRef, SpecializedMeshPipelineError,
+        VertexFormat,
+    },
+    texture::Image,
+};
+
+/// The world space Egui shader handle.
+pub const EGUI_WORLD_SPACE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(9170328640871253015);
+
+/// Position of a vertex in Egui points, used for clipping.
+pub const ATTRIBUTE_EGUI_POSITION: MeshVertexAttribute =
+    MeshVertexAttribute::new("Egui_Position", 1812369041, VertexFormat::Float32x2);
+
+/// Clip rect of a vertex in Egui points (min x, min y, max x, max y).
+pub const ATTRIBUTE_EGUI_CLIP_RECT: MeshVertexAttribute =
+    MeshVertexAttribute::new("Egui_ClipRect", 1812369042, VertexFormat::Float32x4);
+
+/// Draws an Egui context as meshes placed in the 3D world, instead of rendering it to a window,
+/// a texture or a camera viewport.
+///
+/// The shapes are converted to meshes with an [`EguiWorldSpaceMaterial`], spawned as children
+/// of the entity, so the UI stays sharp at any angle, is depth tested against the scene and
+/// clip rects are applied in the fragment shader. The UI is centered at the entity origin,
+/// facing `+Z`, and needs the entity to have a [`SpatialBundle`](bevy_render::prelude::SpatialBundle).
+///
+/// Window input isn't forwarded to world space contexts,
+/// events can be pushed to their [`EguiInput`] manually.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{world_space::EguiWorldSpaceContext, EguiContexts};
+///
+/// #[derive(Component)]
+/// struct Panel;
+///
+/// fn setup(mut commands: Commands) {
+///     commands.spawn((
+///         SpatialBundle::from_transform(Transform::from_xyz(0.0, 1.0, 0.0)),
+///         EguiWorldSpaceContext::new(Vec2::new(400.0, 300.0)),
+///         Panel,
+///     ));
+/// }
+///
+/// fn ui_system(mut contexts: EguiContexts, panels: Query<Entity, With<Panel>>) {
+///     for panel in panels.iter() {
+///         egui::CentralPanel::default().show(contexts.ctx_for_entity_mut(panel), |ui| {
+///             ui.label("Hello from the world");
+///         });
+///     }
+/// }
+/// ```
+#[derive(Component, Debug)]
+pub struct EguiWorldSpaceContext {
+    /// Size of the UI, in points.
+    pub size: Vec2,
+    /// Size of a point in world units (`0.005` by default).
+    pub units_per_point: f32,
+    /// Resolution the shapes are tessellated and fonts are rasterized at (`2.0` by default).
+    pub pixels_per_point: f32,
+    meshes: Vec<Entity>,
+}
+
+impl Default for EguiWorldSpaceContext {
+    fn default() -> Self {
+        Self::new(Vec2::new(400.0, 300.0))
+    }
+}
+
+impl EguiWorldSpaceContext {
+    /// Constructs the context with the size in points.
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            size,
+            units_per_point: 0.005,
+            pixels_per_point: 2.0,
+            meshes: Vec::new(),
+        }
+    }
+
+    /// Sets [`EguiWorldSpaceContext::units_per_point`].
+    pub fn with_units_per_point(mut self, units_per_point: f32) -> Self {
+        self.units_per_point = units_per_point;
+        self
+    }
+
+    /// Sets [`EguiWorldSpaceContext::pixels_per_point`].
+    pub fn with_pixels_per_point(mut self, pixels_per_point: f32) -> Self {
+        self.pixels_per_point = pixels_per_point;
+        self
+    }
+
+    pub(crate) fn render_target_size(&self) -> RenderTargetSize {
+        RenderTargetSize::new(
+            self.size.x * self.pixels_per_point,
+            self.size.y * self.pixels_per_point,
+            self.pixels_per_point,
+        )
+    }
+}
+
+/// Material of the meshes drawing an [`EguiWorldSpaceContext`].
+///
+/// Colors are expected to be linear and premultiplied, shapes are discarded outside
+/// the [`ATTRIBUTE_EGUI_CLIP_RECT`] of their vertices.
+#[derive(Asset, AsBindGroup, TypePath, Clone, Debug)]
+pub struct EguiWorldSpaceMaterial {
+    /// Egui texture the mesh samples.
+    #[texture(0)]
+    #[sampler(1)]
+    pub texture: Handle<Image>,
+    /// Orders meshes of the same context, which are drawn in the same plane.
+    pub depth_bias: f32,
+}
+
+impl Material for EguiWorldSpaceMaterial {
+    fn vertex_shader() -> ShaderRef {
+        EGUI_WORLD_SPACE_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        EGUI_WORLD_SPACE_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Premultiplied
+    }
+
+    fn depth_bias(&self) -> f32 {
+        self.depth_bias
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.vertex.buffers = vec![layout.0.get_layout(&[
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            Mesh::ATTRIBUTE_UV_0.at_shader_location(1),
+            Mesh::ATTRIBUTE_COLOR.at_shader_location(2),
+            ATTRIBUTE_EGUI_POSITION.at_shader_location(3),
+            ATTRIBUTE_EGUI_CLIP_RECT.at_shader_location(4),
+        ])?];
+        // The UI can be seen from behind.
+        descriptor.primitive.cull_mode = None;
+        Ok(())
+    }
+}
+
+/// Adds bevy_egui components to newly added world space contexts.
+pub fn setup_world_space_contexts_system(
+    mut commands: Commands,
+    new_contexts: Query<Entity, (Added<EguiWorldSpaceContext>, Without<EguiContext>)>,
+) {
+    for entity in new_contexts.iter() {
+        commands.entity(entity).insert((
+            EguiContext::default(),
+            EguiSettings::default(),
+            EguiRenderOutput::default(),
+            EguiInput::default(),
+            EguiFullOutput::default(),
+            EguiOutput::default(),
+            RenderTargetSize::default(),
+        ));
+    }
+}
+
+/// Rebuilds the meshes of world space contexts when their paint jobs or textures change.
+///
+/// Consecutive primitives sharing a texture are merged into one mesh.
+#[allow(clippy::too_many_arguments)]
+pub fn update_world_space_meshes_system(
+    mut commands: Commands,
+    mut contexts: Query<(
+        Entity,
+        &mut EguiWorldSpaceContext,
+        &EguiSettings,
+        Ref<EguiRenderOutput>,
+    )>,
+    egui_managed_textures: Res<EguiManagedTextures>,
+    egui_user_textures: Res<EguiUserTextures>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<EguiWorldSpaceMaterial>>,
+    mesh_handles: Query<(&Handle<Mesh>, &Handle<EguiWorldSpaceMaterial>)>,
+) {
+    let textures_changed = egui_managed_textures.is_changed() || egui_user_textures.is_changed();
+    for (entity, mut context, egui_settings, render_output) in contexts.iter_mut() {
+        if !textures_changed && !context.is_changed() && !render_output.is_changed() {
+            continue;
+        }
+
+        let units_per_point = context.units_per_point * egui_settings.scale_factor;
+        let origin = Vec2::new(-context.size.x, context.size.y) * 0.5 * context.units_per_point;
+
+        let mut batches: Vec<(egui::TextureId, MeshBuilder)> = Vec::new();
+        for egui::ClippedPrimitive {
+            clip_rect,
+            primitive,
+        } in render_output.paint_jobs.iter()
+        {
+            let egui::epaint::Primitive::Mesh(mesh) = primitive else {
+                bevy_log::warn_once!("Paint callbacks aren't supported by world space contexts");
+                continue;
+            };
+            let batch = match batches.last_mut() {
+                Some((texture_id, batch)) if *texture_id == mesh.texture_id => batch,
+                _ => {
+                    batches.push((mesh.texture_id, MeshBuilder::default()));
+                    &mut batches.last_mut().unwrap().1
+                }
+            };
+            batch.append(mesh, *clip_rect, origin, units_per_point);
+        }
+
+        let batches: Vec<_> = batches
+            .into_iter()
+            .filter_map(|(texture_id, batch)| {
+                let texture = match texture_id {
+                    egui::TextureId::Managed(id) => egui_managed_textures
+                        .get(&(entity, id))
+                        .map(|texture| texture.handle.clone()),
+                    egui::TextureId::User(id) => egui_user_textures.image(id).cloned(),
+                };
+                if texture.is_none() {
+                    bevy_log::debug!("Skipping a mesh with a missing texture ({texture_id:?})");
+                }
+                Some((texture?, batch))
+            })
+            .collect();
+
+        // Storing the mesh entities shouldn't trigger rebuilding them once again.
+        let context = context.bypass_change_detection();
+        let batch_count = batches.len();
+        for (index, (texture, batch)) in batches.into_iter().enumerate() {
+            let aabb = batch.aabb();
+            let mesh = batch.build();
+            let material = EguiWorldSpaceMaterial {
+                texture,
+                depth_bias: index as f32 * DEPTH_BIAS_STEP,
+            };
+
+            // Reuse the entities and assets of the previous meshes.
+            if let Some(&mesh_entity) = context.meshes.get(index) {
+                if let Ok((mesh_handle, material_handle)) = mesh_handles.get(mesh_entity) {
+                    meshes.insert(mesh_handle, mesh);
+                    materials.insert(material_handle, material);
+                    commands.entity(mesh_entity).insert(aabb);
+                    continue;
+                }
+            }
+
+            let mesh_entity = commands
+                .spawn((
+                    MaterialMeshBundle {
+                        mesh: meshes.add(mesh),
+                        material: materials.add(material),
+                        ..Default::default()
+                    },
+                    aabb,
+                ))
+                .set_parent(entity)
+                .id();
+            match context.meshes.get_mut(index) {
+                Some(despawned_entity) => *despawned_entity = mesh_entity,
+                None => context.meshes.push(mesh_entity),
+            }
+        }
+        for mesh_entity in context
+            .meshes
+            .drain(batch_count.min(context.meshes.len())..)
+        {
+            if let Some(mesh_entity) = commands.get_entity(mesh_entity) {
+                mesh_entity.despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Meshes of a context are coplanar, so they are sorted by their depth bias only.
+const DEPTH_BIAS_STEP: f32 = 0.001;
+
+#[derive(Default)]
+struct MeshBuilder {
+    positions: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    colors: Vec<[f32; 4]>,
+    points: Vec<[f32; 2]>,
+    clip_rects: Vec<[f32; 4]>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    fn append(
+        &mut self,
+        mesh: &egui::Mesh,
+        clip_rect: egui::Rect,
+        origin: Vec2,
+        units_per_point: f32,
+    ) {
+        let first_index = self.positions.len() as u32;
+        self.indices
+            .extend(mesh.indices.iter().map(|index| first_index + index));
+        for vertex in &mesh.vertices {
+            let position = origin + Vec2::new(vertex.pos.x, -vertex.pos.y) * units_per_point;
+            self.positions.push([position.x, position.y, 0.0]);
+            self.uvs.push([vertex.uv.x, vertex.uv.y]);
+            self.colors.push(egui::Rgba::from(vertex.color).to_array());
+            self.points.push([vertex.pos.x, vertex.pos.y]);
+            self.clip_rects.push([
+                clip_rect.min.x,
+                clip_rect.min.y,
+                clip_rect.max.x,
+                clip_rect.max.y,
+            ]);
+        }
+    }
+
+    fn aabb(&self) -> Aabb {
+        let (min, max) = self.positions.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), &position| (min.min(position.into()), max.max(position.into())),
+        );
+        Aabb::from_min_max(min, max)
+    }
+
+    fn build(self) -> Mesh {
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::RENDER_WORLD,
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, self.colors)
+        .with_inserted_attribute(ATTRIBUTE_EGUI_POSITION, self.points)
+        .with_inserted_attribute(ATTRIBUTE_EGUI_CLIP_RECT, self.clip_rects)
+        .with_inserted_indices(Indices::U32(self.indices))
+    }
+}